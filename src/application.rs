@@ -80,7 +80,8 @@ pub trait Application: Sized {
                 .into();
             let mut ui = UserInterface::build(view, cache.take().unwrap(), &mut renderer);
 
-            // Displays the new state of the sandbox using the renderer
+            // Displays the new state of the sandbox using the renderer.
+            // Only the parts of the screen that changed are printed again
             let primitives = ui.draw(&mut renderer);
             renderer.draw(primitives);
 
@@ -92,10 +93,6 @@ pub trait Application: Sized {
                     ui.update(&renderer, None, events.into_iter())
                 })
                 .unwrap_or(vec![]);
-            if messages.len() != 0 {
-                renderer.flush();
-            }
-
             // Polls Application::Messages from the Receiver
             let mut evt_queue = event_queue.lock().expect("Poisoned lock");
             let mut events = evt_queue.take().unwrap();
@@ -0,0 +1,216 @@
+use crate::backend::{input, Backend, DEFAULT_CELL_SIZE};
+use crate::colors::{ColorPalette, ColorRegistry, ColorRegistryStats, PancursesColor};
+use crate::style::Style;
+use iced_native::Event;
+use pancurses::{initscr, Input, Window};
+use std::io::Write;
+
+/// Escape sequence enabling the report of all mouse motions by xterm compatible terminals
+const ENABLE_MOTION_TRACKING: &str = "\x1b[?1003h";
+/// Escape sequence restoring the default mouse tracking
+const DISABLE_MOTION_TRACKING: &str = "\x1b[?1003l";
+
+/// Backend printing to the terminal through pancurses
+pub struct PancursesBackend {
+    /// Pancurses window to use to print UI elements
+    window: Window,
+    /// Translation of colors into the ones supported by the terminal
+    palette: ColorPalette,
+    /// The ColorRegistry is the place to store pancurses color pairs indices
+    color_registry: ColorRegistry,
+    /// Style of the last printed character, to avoid resetting the attributes for each cell
+    current_style: Option<Style>,
+    /// Whether polling returns immediately when there is no input
+    nodelay: bool,
+}
+
+impl Default for PancursesBackend {
+    /// Default config for a Pancurses backend
+    fn default() -> Self {
+        // Escape is used to start key sequences: reduce the time spent waiting for the rest
+        // of a sequence, so that the escape key itself is reported quickly
+        if std::env::var_os("ESCDELAY").is_none() {
+            std::env::set_var("ESCDELAY", "25");
+        }
+        let window = initscr();
+        pancurses::noecho();
+        pancurses::curs_set(0);
+        pancurses::start_color();
+        pancurses::use_default_colors();
+        // Set keypad mode; necessary for correct input handling
+        window.keypad(true);
+
+        // Listen to all mouse events, including motion, in order to support dragging
+        pancurses::mousemask(
+            pancurses::ALL_MOUSE_EVENTS | pancurses::REPORT_MOUSE_POSITION,
+            std::ptr::null_mut(),
+        );
+        // Report presses and releases as they happen instead of waiting to detect clicks
+        pancurses::mouseinterval(0);
+        // Ask xterm compatible terminals to report every motion, even with no button held
+        print!("{}", ENABLE_MOTION_TRACKING);
+        let _ = std::io::stdout().flush();
+        Self {
+            window,
+            palette: ColorPalette::detect(),
+            color_registry: ColorRegistry::detect(),
+            current_style: None,
+            nodelay: false,
+        }
+    }
+}
+
+impl Drop for PancursesBackend {
+    /// Gives the terminal back in the state it was found
+    fn drop(&mut self) {
+        print!("{}", DISABLE_MOTION_TRACKING);
+        let _ = std::io::stdout().flush();
+        pancurses::endwin();
+    }
+}
+
+/// Gets the pancurses attributes matching the text attributes of the style
+fn attributes(style: Style) -> pancurses::chtype {
+    [
+        (style.bold, pancurses::A_BOLD),
+        (style.underline, pancurses::A_UNDERLINE),
+        (style.reverse, pancurses::A_REVERSE),
+        (style.dim, pancurses::A_DIM),
+        (style.italic, pancurses::A_ITALIC),
+        (style.blink, pancurses::A_BLINK),
+    ]
+    .iter()
+    .filter(|(enabled, _)| *enabled)
+    .fold(pancurses::A_NORMAL, |attributes, (_, attribute)| {
+        attributes | attribute
+    })
+}
+
+impl Backend for PancursesBackend {
+    fn size(&self) -> (u16, u16) {
+        let yx = self.window.get_max_yx();
+        (yx.1 as u16, yx.0 as u16)
+    }
+
+    fn clear(&mut self) {
+        self.window.clear();
+        self.color_registry.clear();
+    }
+
+    fn put_char(&mut self, x: u16, y: u16, content: &str, style: Style) {
+        // The cell is covered by the wide character printed on its left
+        if content.is_empty() {
+            return;
+        }
+        if self.current_style != Some(style) {
+            let fg = self.palette.get_color(style.foreground);
+            let bg = match style.background {
+                Some(background) => self.palette.get_color(background),
+                None => -1,
+            };
+            let col_idx = self
+                .color_registry
+                .get_idx(PancursesColor::new(fg, bg), &self.palette);
+            self.window
+                .attrset(pancurses::COLOR_PAIR((col_idx as u32).into()) | attributes(style));
+            self.current_style = Some(style);
+        }
+        let (x, y) = (x as i32, y as i32);
+        // Box drawing characters are printed using the alternative charset, which is
+        // available even on terminals that do not handle unicode
+        match content {
+            "┌" => self.window.mvaddch(y, x, pancurses::ACS_ULCORNER()),
+            "┐" => self.window.mvaddch(y, x, pancurses::ACS_URCORNER()),
+            "└" => self.window.mvaddch(y, x, pancurses::ACS_LLCORNER()),
+            "┘" => self.window.mvaddch(y, x, pancurses::ACS_LRCORNER()),
+            "─" => self.window.mvaddch(y, x, pancurses::ACS_HLINE()),
+            "│" => self.window.mvaddch(y, x, pancurses::ACS_VLINE()),
+            _ => self.window.mvaddstr(y, x, content),
+        };
+    }
+
+    fn set_cursor(&mut self, position: Option<(u16, u16)>) {
+        match position {
+            Some((x, y)) => {
+                pancurses::curs_set(1);
+                self.window.mv(y as i32, x as i32);
+            }
+            None => {
+                pancurses::curs_set(0);
+            }
+        }
+    }
+
+    fn refresh(&mut self) {
+        self.window.refresh();
+    }
+
+    fn write_raw(&mut self, x: u16, y: u16, sequence: &str) {
+        self.window.refresh();
+        // The cursor is saved and restored around the sequence, for pancurses to keep
+        // knowing where it is
+        print!("\x1b7\x1b[{};{}H{}\x1b8", y + 1, x + 1, sequence);
+        let _ = std::io::stdout().flush();
+    }
+
+    #[cfg(unix)]
+    fn cell_size(&self) -> (u16, u16) {
+        // The size of the terminal in pixels is not reported by every terminal
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        let found = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+        if found && size.ws_col > 0 && size.ws_row > 0 && size.ws_xpixel > 0 && size.ws_ypixel > 0 {
+            (size.ws_xpixel / size.ws_col, size.ws_ypixel / size.ws_row)
+        } else {
+            DEFAULT_CELL_SIZE
+        }
+    }
+
+    fn poll_events(&mut self) -> Option<Vec<Event>> {
+        match self.window.getch()? {
+            // Alt combinations are received as an escape followed by the key
+            Input::Character('\x1b') => {
+                self.window.nodelay(true);
+                let next = self.window.getch();
+                self.window.nodelay(self.nodelay);
+                let escape = input::translate_key(Input::Character('\x1b'));
+                match next {
+                    // Mouse reports and resizes do not combine with alt: the escape was pressed
+                    // on its own, just before them
+                    Some(next @ Input::KeyMouse) | Some(next @ Input::KeyResize) => {
+                        let mut events = escape.unwrap_or_default();
+                        events.extend(translate_input(next).unwrap_or_default());
+                        Some(events)
+                    }
+                    Some(next) => input::translate_key(next).map(input::with_alt),
+                    None => escape,
+                }
+            }
+            input => translate_input(input),
+        }
+    }
+
+    fn set_nodelay(&mut self, nodelay: bool) {
+        self.nodelay = nodelay;
+        self.window.nodelay(nodelay);
+    }
+
+    fn take_invalidated(&mut self) -> bool {
+        self.color_registry.take_evicted()
+    }
+
+    fn color_stats(&self) -> Option<ColorRegistryStats> {
+        Some(self.color_registry.stats())
+    }
+}
+
+/// Translates an input received from pancurses into iced events
+fn translate_input(input: Input) -> Option<Vec<Event>> {
+    match input {
+        // The renderer notices the change of size, and redraws the whole screen
+        Input::KeyResize => None,
+        Input::KeyMouse => pancurses::getmouse()
+            .ok()
+            .map(|event| input::translate_mouse(event.bstate, event.x, event.y)),
+        input => input::translate_key(input),
+    }
+}
@@ -0,0 +1,357 @@
+use crate::backend::Backend;
+use crate::frame::{Cell, Frame};
+use crate::style::Style;
+use iced_native::{Color, Event};
+use std::collections::VecDeque;
+
+/// In-memory backend, recording the drawn cells instead of printing them to a terminal.
+///
+/// Events are scripted: they are given beforehand using `push_events`, and handed out
+/// one batch at a time to the renderer.
+/// This allows to run UIs in tests, without a terminal.
+#[derive(Debug)]
+pub struct HeadlessBackend {
+    /// Cells of the screen
+    frame: Frame,
+    /// Position of the cursor, if it is shown
+    cursor: Option<(u16, u16)>,
+    /// Batches of events waiting to be polled
+    events: VecDeque<Vec<Event>>,
+    /// Escape sequences written since the screen was last cleared, with their position
+    raw: Vec<(u16, u16, String)>,
+}
+
+impl HeadlessBackend {
+    /// Creates a blank screen of the given size
+    pub fn new(width: u16, height: u16) -> Self {
+        HeadlessBackend {
+            frame: Frame::new(width, height),
+            cursor: None,
+            events: VecDeque::new(),
+            raw: Vec::new(),
+        }
+    }
+
+    /// Gets the cell at the given position, if it is on the screen
+    pub fn cell(&self, x: u16, y: u16) -> Option<&Cell> {
+        self.frame.get(x, y)
+    }
+
+    /// Gets the position of the cursor, if it is shown
+    pub fn cursor(&self) -> Option<(u16, u16)> {
+        self.cursor
+    }
+
+    /// Gets the escape sequences drawing images written since the screen was last cleared,
+    /// with their position
+    pub fn raw_writes(&self) -> &[(u16, u16, String)] {
+        &self.raw
+    }
+
+    /// Gets the characters of the given line of the screen
+    pub fn line(&self, y: u16) -> String {
+        let (width, _) = self.frame.size();
+        (0..width)
+            .filter_map(|x| self.cell(x, y))
+            .map(|cell| cell.content.as_str())
+            .collect()
+    }
+
+    /// Gets the characters of every line of the screen
+    pub fn lines(&self) -> Vec<String> {
+        let (_, height) = self.frame.size();
+        (0..height).map(|y| self.line(y)).collect()
+    }
+
+    /// Gets the screen as plain text, one line per screen line
+    pub fn text(&self) -> String {
+        self.lines().join("\n")
+    }
+
+    /// Gets the screen as text colored with ANSI escape sequences, one line per screen line.
+    ///
+    /// Colors are written as 24-bit colors, the style being reset at the end of each line.
+    pub fn ansi(&self) -> String {
+        let (width, height) = self.frame.size();
+        (0..height)
+            .map(|y| {
+                let mut line = String::new();
+                let mut current_style = None;
+                (0..width).filter_map(|x| self.cell(x, y)).for_each(|cell| {
+                    if current_style != Some(cell.style) {
+                        line.push_str(&ansi_style(cell.style));
+                        current_style = Some(cell.style);
+                    }
+                    line.push_str(&cell.content);
+                });
+                line.push_str("\x1b[0m");
+                line
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Changes the size of the screen, clearing it
+    pub fn resize(&mut self, width: u16, height: u16) {
+        *self = HeadlessBackend {
+            events: std::mem::take(&mut self.events),
+            ..HeadlessBackend::new(width, height)
+        };
+    }
+
+    /// Queues a batch of events, to be returned by a later poll
+    pub fn push_events(&mut self, events: Vec<Event>) {
+        self.events.push_back(events);
+    }
+}
+
+/// Gets the ANSI escape sequence selecting the given style
+fn ansi_style(style: Style) -> String {
+    let attributes: String = [
+        (style.bold, ";1"),
+        (style.dim, ";2"),
+        (style.italic, ";3"),
+        (style.underline, ";4"),
+        (style.blink, ";5"),
+        (style.reverse, ";7"),
+    ]
+    .iter()
+    .filter(|(enabled, _)| *enabled)
+    .map(|(_, code)| *code)
+    .collect();
+    let to_rgb = |color: Color| {
+        (
+            (color.r * 255.).round() as u8,
+            (color.g * 255.).round() as u8,
+            (color.b * 255.).round() as u8,
+        )
+    };
+    let (r, g, b) = to_rgb(style.foreground);
+    let background = match style.background {
+        Some(color) => {
+            let (r, g, b) = to_rgb(color);
+            format!("48;2;{};{};{}", r, g, b)
+        }
+        None => "49".into(),
+    };
+    format!(
+        "\x1b[0{};38;2;{};{};{};{}m",
+        attributes, r, g, b, background
+    )
+}
+
+impl Backend for HeadlessBackend {
+    fn size(&self) -> (u16, u16) {
+        self.frame.size()
+    }
+
+    fn clear(&mut self) {
+        self.frame.clear();
+        self.raw.clear();
+    }
+
+    fn put_char(&mut self, x: u16, y: u16, content: &str, style: Style) {
+        let content = content.into();
+        self.frame.set(x, y, Cell { content, style });
+    }
+
+    fn set_cursor(&mut self, position: Option<(u16, u16)>) {
+        self.cursor = position;
+    }
+
+    fn refresh(&mut self) {}
+
+    fn write_raw(&mut self, x: u16, y: u16, sequence: &str) {
+        self.raw.push((x, y, sequence.into()));
+    }
+
+    fn poll_events(&mut self) -> Option<Vec<Event>> {
+        self.events.pop_front()
+    }
+
+    fn set_nodelay(&mut self, _nodelay: bool) {}
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use crate::primitive::Primitive;
+    use crate::style::Style;
+    use crate::PancursesRenderer;
+    use iced_native::input::keyboard;
+    use iced_native::{Color, Event, Rectangle};
+
+    #[test]
+    pub fn headless_backend_should_record_drawing() {
+        let mut renderer = PancursesRenderer::headless(8, 4);
+        renderer.draw(Primitive::Group(vec![
+            Primitive::BoxDisplay(
+                Rectangle {
+                    x: 0.,
+                    y: 0.,
+                    width: 6.,
+                    height: 3.,
+                },
+                Style::default(),
+            ),
+            Primitive::Text(
+                vec!["Hey!".into()],
+                Rectangle {
+                    x: 1.,
+                    y: 1.,
+                    width: 4.,
+                    height: 1.,
+                },
+                Style::new(Color::BLACK),
+            ),
+        ]));
+        // The screen should look like this, the text being black:
+        //
+        // ┌────┐
+        // │Hey!│
+        // └────┘
+        let backend = renderer.headless_backend().unwrap();
+        assert_eq!(
+            backend.lines(),
+            vec!["┌────┐  ", "│Hey!│  ", "└────┘  ", "        "]
+        );
+        assert_eq!(backend.cell(2, 1).unwrap().style.foreground, Color::BLACK);
+        assert_eq!(backend.cell(0, 1).unwrap().style.foreground, Color::WHITE);
+    }
+
+    #[test]
+    pub fn headless_backend_should_export_ansi() {
+        let mut renderer = PancursesRenderer::headless(3, 2);
+        renderer.draw(Primitive::Text(
+            vec!["ab".into()],
+            Rectangle {
+                x: 1.,
+                y: 1.,
+                width: 2.,
+                height: 1.,
+            },
+            Style::new(Color {
+                r: 1.,
+                g: 0.,
+                b: 0.,
+                a: 1.,
+            }),
+        ));
+        let backend = renderer.headless_backend().unwrap();
+        assert_eq!(backend.text(), "   \n ab");
+        assert_eq!(
+            backend.ansi(),
+            "\x1b[0;38;2;255;255;255;49m   \x1b[0m\n\
+             \x1b[0;38;2;255;255;255;49m \x1b[0;38;2;255;0;0;49mab\x1b[0m"
+        );
+    }
+
+    #[test]
+    pub fn headless_backend_should_clip_drawing() {
+        let mut renderer = PancursesRenderer::headless(3, 1);
+        renderer.draw(Primitive::Text(
+            vec!["Too long".into(), "Not there".into()],
+            Rectangle {
+                x: -1.,
+                y: 0.,
+                width: 8.,
+                height: 2.,
+            },
+            Style::default(),
+        ));
+        assert_eq!(renderer.headless_backend().unwrap().lines(), vec!["oo "]);
+    }
+
+    #[test]
+    pub fn headless_backend_should_clip_to_rectangle() {
+        let mut renderer = PancursesRenderer::headless(5, 3);
+        renderer.draw(Primitive::Clip(
+            Rectangle {
+                x: 1.,
+                y: 1.,
+                width: 3.,
+                height: 1.,
+            },
+            Box::new(Primitive::Text(
+                vec!["Above".into(), "Cut me".into(), "Below".into()],
+                Rectangle {
+                    x: 0.,
+                    y: 0.,
+                    width: 6.,
+                    height: 3.,
+                },
+                Style::default(),
+            )),
+        ));
+        // Only the middle of the line straddling the clipping area is drawn
+        assert_eq!(
+            renderer.headless_backend().unwrap().lines(),
+            vec!["     ", " ut  ", "     "]
+        );
+    }
+
+    #[test]
+    pub fn headless_backend_should_blank_cut_wide_characters() {
+        let mut renderer = PancursesRenderer::headless(4, 2);
+        let text = |x, y| {
+            Primitive::Text(
+                vec!["日本".into()],
+                Rectangle {
+                    x,
+                    y,
+                    width: 4.,
+                    height: 1.,
+                },
+                Style::default(),
+            )
+        };
+        // Wide characters starting off the screen or the clipping area leave a space
+        // on the columns that are drawn
+        renderer.draw(Primitive::Group(vec![
+            text(-1., 0.),
+            Primitive::Clip(
+                Rectangle {
+                    x: 1.,
+                    y: 1.,
+                    width: 3.,
+                    height: 1.,
+                },
+                Box::new(text(0., 1.)),
+            ),
+        ]));
+        assert_eq!(
+            renderer.headless_backend().unwrap().lines(),
+            vec![" 本 ", "  本"]
+        );
+    }
+
+    #[test]
+    pub fn headless_backend_should_export_attributes() {
+        let mut renderer = PancursesRenderer::headless(1, 1);
+        renderer.draw(Primitive::Char(
+            0,
+            0,
+            'a',
+            Style::new(Color::BLACK)
+                .background(Color::WHITE)
+                .bold()
+                .underline(),
+        ));
+        assert_eq!(
+            renderer.headless_backend().unwrap().ansi(),
+            "\x1b[0;1;4;38;2;0;0;0;48;2;255;255;255ma\x1b[0m"
+        );
+    }
+
+    #[test]
+    pub fn headless_backend_should_replay_events() {
+        let mut renderer = PancursesRenderer::headless(1, 1);
+        let event = Event::Keyboard(keyboard::Event::CharacterReceived('a'));
+        renderer
+            .headless_backend_mut()
+            .unwrap()
+            .push_events(vec![event]);
+        assert_eq!(renderer.handle(), Some(vec![event]));
+        assert_eq!(renderer.handle(), None);
+    }
+}
@@ -0,0 +1,616 @@
+//! Translation of the pancurses inputs into iced events
+
+use iced_native::input::{
+    keyboard, keyboard::KeyCode, keyboard::ModifiersState, mouse, mouse::Button, ButtonState,
+};
+use iced_native::Event;
+use pancurses::{mmask_t, Input};
+
+/// Number of lines scrolled by a step of the mouse wheel
+const WHEEL_SCROLL_LINES: f32 = 3.;
+/// Code of the F0 key in curses: the code of the Fn key is `KEY_F0 + n`
+const KEY_F0: i32 = 0o410;
+/// Function keys, from F1 to F12
+const FUNCTION_KEYS: [KeyCode; 12] = [
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+];
+
+/// Translates a keyboard input into iced events.
+///
+/// Keys are translated to a press immediately followed by a release, as terminals do not report
+/// releases. Printable characters also produce a `CharacterReceived` event.
+pub fn translate_key(input: Input) -> Option<Vec<Event>> {
+    match input {
+        Input::Character(c) => Some(translate_char(c)),
+        // Pancurses does not know the function keys past F15
+        Input::Unknown(code) => translate_function_key(code - KEY_F0)
+            .or_else(|| pancurses::keyname(code).and_then(|name| translate_key_name(&name)))
+            .map(|(key_code, modifiers)| key_press(key_code, modifiers)),
+        _ => {
+            translate_special_key(input).map(|(key_code, modifiers)| key_press(key_code, modifiers))
+        }
+    }
+}
+
+/// Adds the alt modifier to translated events.
+///
+/// Terminals send alt combinations as an escape followed by the key itself: the characters
+/// are dropped, as they are not meant to be typed.
+pub fn with_alt(events: Vec<Event>) -> Vec<Event> {
+    events
+        .into_iter()
+        .filter_map(|event| match event {
+            Event::Keyboard(keyboard::Event::Input {
+                state,
+                key_code,
+                modifiers,
+            }) => Some(Event::Keyboard(keyboard::Event::Input {
+                state,
+                key_code,
+                modifiers: ModifiersState {
+                    alt: true,
+                    ..modifiers
+                },
+            })),
+            Event::Keyboard(keyboard::Event::CharacterReceived(_)) => None,
+            event => Some(event),
+        })
+        .collect()
+}
+
+/// Translates a mouse input into iced events.
+///
+/// The cursor is always moved to the position of the input first, which makes motion reports
+/// and drags work. Clicks are translated to presses immediately followed by releases.
+pub fn translate_mouse(bstate: mmask_t, x: i32, y: i32) -> Vec<Event> {
+    let buttons = [(Button::Left, 1), (Button::Middle, 2), (Button::Right, 3)];
+    let mut events = vec![Event::Mouse(mouse::Event::CursorMoved {
+        x: x as f32,
+        y: y as f32,
+    })];
+    for (button, number) in buttons.iter() {
+        let masks = button_masks(*number);
+        if bstate & masks.pressed != 0 {
+            events.push(button_event(ButtonState::Pressed, *button));
+        }
+        if bstate & masks.released != 0 {
+            events.push(button_event(ButtonState::Released, *button));
+        }
+        let clicks = if bstate & masks.triple_clicked != 0 {
+            3
+        } else if bstate & masks.double_clicked != 0 {
+            2
+        } else if bstate & masks.clicked != 0 {
+            1
+        } else {
+            0
+        };
+        for _ in 0..clicks {
+            events.push(button_event(ButtonState::Pressed, *button));
+            events.push(button_event(ButtonState::Released, *button));
+        }
+    }
+    // The wheel is reported as the presses of buttons 4 (up) and 5 (down)
+    if bstate & pancurses::BUTTON4_PRESSED != 0 {
+        events.push(wheel_event(WHEEL_SCROLL_LINES));
+    }
+    if bstate & pancurses::BUTTON5_PRESSED != 0 {
+        events.push(wheel_event(-WHEEL_SCROLL_LINES));
+    }
+    events
+}
+
+/// Masks of the states of a mouse button
+struct ButtonMasks {
+    pressed: mmask_t,
+    released: mmask_t,
+    clicked: mmask_t,
+    double_clicked: mmask_t,
+    triple_clicked: mmask_t,
+}
+
+fn button_masks(number: u8) -> ButtonMasks {
+    match number {
+        1 => ButtonMasks {
+            pressed: pancurses::BUTTON1_PRESSED,
+            released: pancurses::BUTTON1_RELEASED,
+            clicked: pancurses::BUTTON1_CLICKED,
+            double_clicked: pancurses::BUTTON1_DOUBLE_CLICKED,
+            triple_clicked: pancurses::BUTTON1_TRIPLE_CLICKED,
+        },
+        2 => ButtonMasks {
+            pressed: pancurses::BUTTON2_PRESSED,
+            released: pancurses::BUTTON2_RELEASED,
+            clicked: pancurses::BUTTON2_CLICKED,
+            double_clicked: pancurses::BUTTON2_DOUBLE_CLICKED,
+            triple_clicked: pancurses::BUTTON2_TRIPLE_CLICKED,
+        },
+        _ => ButtonMasks {
+            pressed: pancurses::BUTTON3_PRESSED,
+            released: pancurses::BUTTON3_RELEASED,
+            clicked: pancurses::BUTTON3_CLICKED,
+            double_clicked: pancurses::BUTTON3_DOUBLE_CLICKED,
+            triple_clicked: pancurses::BUTTON3_TRIPLE_CLICKED,
+        },
+    }
+}
+
+fn button_event(state: ButtonState, button: Button) -> Event {
+    Event::Mouse(mouse::Event::Input { state, button })
+}
+
+/// Creates a wheel event scrolling the given number of lines, upwards when positive.
+/// Lines are given as pixels, as a pixel is a cell for this renderer.
+fn wheel_event(lines: f32) -> Event {
+    Event::Mouse(mouse::Event::WheelScrolled {
+        delta: mouse::ScrollDelta::Pixels { x: 0., y: lines },
+    })
+}
+
+/// Translates a character received from the terminal
+fn translate_char(c: char) -> Vec<Event> {
+    match c {
+        '\n' | '\r' => key_press(KeyCode::Enter, modifiers(false, false, false)),
+        '\t' => key_press(KeyCode::Tab, modifiers(false, false, false)),
+        '\x1b' => key_press(KeyCode::Escape, modifiers(false, false, false)),
+        '\x08' | '\x7f' => key_press(KeyCode::Backspace, modifiers(false, false, false)),
+        // Control combinations are received as the control characters 1 to 26
+        '\x01'..='\x1a' => {
+            let letter = (b'a' + c as u8 - 1) as char;
+            match char_key_code(letter) {
+                Some((key_code, _)) => key_press(key_code, modifiers(false, true, false)),
+                None => vec![],
+            }
+        }
+        _ if c.is_control() => vec![],
+        _ => match char_key_code(c) {
+            Some((key_code, shift)) => {
+                let modifiers = modifiers(shift, false, false);
+                vec![
+                    key_event(ButtonState::Pressed, key_code, modifiers),
+                    Event::Keyboard(keyboard::Event::CharacterReceived(c)),
+                    key_event(ButtonState::Released, key_code, modifiers),
+                ]
+            }
+            None => vec![Event::Keyboard(keyboard::Event::CharacterReceived(c))],
+        },
+    }
+}
+
+/// Gets the key producing the given character, and whether shift has to be held to produce it
+fn char_key_code(c: char) -> Option<(KeyCode, bool)> {
+    const LETTERS: [KeyCode; 26] = [
+        KeyCode::A,
+        KeyCode::B,
+        KeyCode::C,
+        KeyCode::D,
+        KeyCode::E,
+        KeyCode::F,
+        KeyCode::G,
+        KeyCode::H,
+        KeyCode::I,
+        KeyCode::J,
+        KeyCode::K,
+        KeyCode::L,
+        KeyCode::M,
+        KeyCode::N,
+        KeyCode::O,
+        KeyCode::P,
+        KeyCode::Q,
+        KeyCode::R,
+        KeyCode::S,
+        KeyCode::T,
+        KeyCode::U,
+        KeyCode::V,
+        KeyCode::W,
+        KeyCode::X,
+        KeyCode::Y,
+        KeyCode::Z,
+    ];
+    const DIGITS: [KeyCode; 10] = [
+        KeyCode::Key0,
+        KeyCode::Key1,
+        KeyCode::Key2,
+        KeyCode::Key3,
+        KeyCode::Key4,
+        KeyCode::Key5,
+        KeyCode::Key6,
+        KeyCode::Key7,
+        KeyCode::Key8,
+        KeyCode::Key9,
+    ];
+    match c {
+        'a'..='z' => Some((LETTERS[(c as u8 - b'a') as usize], false)),
+        'A'..='Z' => Some((LETTERS[(c as u8 - b'A') as usize], true)),
+        '0'..='9' => Some((DIGITS[(c as u8 - b'0') as usize], false)),
+        ' ' => Some((KeyCode::Space, false)),
+        '\'' => Some((KeyCode::Apostrophe, false)),
+        ',' => Some((KeyCode::Comma, false)),
+        '-' => Some((KeyCode::Minus, false)),
+        '.' => Some((KeyCode::Period, false)),
+        '/' => Some((KeyCode::Slash, false)),
+        ';' => Some((KeyCode::Semicolon, false)),
+        '=' => Some((KeyCode::Equals, false)),
+        '[' => Some((KeyCode::LBracket, false)),
+        '\\' => Some((KeyCode::Backslash, false)),
+        ']' => Some((KeyCode::RBracket, false)),
+        '`' => Some((KeyCode::Grave, false)),
+        '*' => Some((KeyCode::Multiply, true)),
+        '+' => Some((KeyCode::Add, true)),
+        ':' => Some((KeyCode::Colon, true)),
+        '@' => Some((KeyCode::At, true)),
+        '^' => Some((KeyCode::Caret, true)),
+        '_' => Some((KeyCode::Underline, true)),
+        _ => None,
+    }
+}
+
+/// Translates the special keys known by pancurses
+fn translate_special_key(input: Input) -> Option<(KeyCode, ModifiersState)> {
+    let plain = modifiers(false, false, false);
+    let shifted = modifiers(true, false, false);
+    let key = match input {
+        Input::KeyLeft => (KeyCode::Left, plain),
+        Input::KeyRight => (KeyCode::Right, plain),
+        Input::KeyUp => (KeyCode::Up, plain),
+        Input::KeyDown => (KeyCode::Down, plain),
+        Input::KeySLeft => (KeyCode::Left, shifted),
+        Input::KeySRight => (KeyCode::Right, shifted),
+        // Shift + Up and Down are known as scroll reverse and scroll forward
+        Input::KeySR => (KeyCode::Up, shifted),
+        Input::KeySF => (KeyCode::Down, shifted),
+        Input::KeyHome | Input::KeyA1 => (KeyCode::Home, plain),
+        Input::KeySHome => (KeyCode::Home, shifted),
+        Input::KeyEnd | Input::KeyC1 => (KeyCode::End, plain),
+        Input::KeySEnd => (KeyCode::End, shifted),
+        Input::KeyPPage | Input::KeyA3 => (KeyCode::PageUp, plain),
+        Input::KeySPrevious => (KeyCode::PageUp, shifted),
+        Input::KeyNPage | Input::KeyC3 => (KeyCode::PageDown, plain),
+        Input::KeySNext => (KeyCode::PageDown, shifted),
+        Input::KeyDC => (KeyCode::Delete, plain),
+        Input::KeySDC => (KeyCode::Delete, shifted),
+        Input::KeyIC => (KeyCode::Insert, plain),
+        Input::KeySIC => (KeyCode::Insert, shifted),
+        Input::KeyBackspace => (KeyCode::Backspace, plain),
+        // Text inputs are only submitted with Enter
+        Input::KeyEnter => (KeyCode::Enter, plain),
+        Input::KeyBTab => (KeyCode::Tab, shifted),
+        Input::KeyBreak => (KeyCode::Pause, plain),
+        Input::KeyPrint => (KeyCode::Snapshot, plain),
+        Input::KeyF1 => (KeyCode::F1, plain),
+        Input::KeyF2 => (KeyCode::F2, plain),
+        Input::KeyF3 => (KeyCode::F3, plain),
+        Input::KeyF4 => (KeyCode::F4, plain),
+        Input::KeyF5 => (KeyCode::F5, plain),
+        Input::KeyF6 => (KeyCode::F6, plain),
+        Input::KeyF7 => (KeyCode::F7, plain),
+        Input::KeyF8 => (KeyCode::F8, plain),
+        Input::KeyF9 => (KeyCode::F9, plain),
+        Input::KeyF10 => (KeyCode::F10, plain),
+        Input::KeyF11 => (KeyCode::F11, plain),
+        Input::KeyF12 => (KeyCode::F12, plain),
+        Input::KeyF13 => return translate_function_key(13),
+        Input::KeyF14 => return translate_function_key(14),
+        Input::KeyF15 => return translate_function_key(15),
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// Translates the function keys past F12, which terminals report for F1 to F12 held with
+/// modifiers: F13 to F24 for Shift, F25 to F36 for Control, F37 to F48 for Control + Shift,
+/// F49 to F60 for Alt, and F61 to F63 for Alt + Shift.
+fn translate_function_key(number: i32) -> Option<(KeyCode, ModifiersState)> {
+    if !(13..=63).contains(&number) {
+        return None;
+    }
+    let key_code = FUNCTION_KEYS[((number - 1) % 12) as usize];
+    let modifiers = match (number - 1) / 12 {
+        1 => modifiers(true, false, false),
+        2 => modifiers(false, true, false),
+        3 => modifiers(true, true, false),
+        4 => modifiers(false, false, true),
+        _ => modifiers(true, false, true),
+    };
+    Some((key_code, modifiers))
+}
+
+/// Translates the extended keys that pancurses does not know, using their terminfo name.
+///
+/// These names are made of the key and of a number describing the held modifiers,
+/// for example "kLFT5" for Control + Left.
+fn translate_key_name(name: &str) -> Option<(KeyCode, ModifiersState)> {
+    let split = name.find(|c: char| c.is_ascii_digit())?;
+    let (key, modifier) = name.split_at(split);
+    let key_code = match key {
+        "kLFT" => KeyCode::Left,
+        "kRIT" => KeyCode::Right,
+        "kUP" => KeyCode::Up,
+        "kDN" => KeyCode::Down,
+        "kHOM" => KeyCode::Home,
+        "kEND" => KeyCode::End,
+        "kPRV" => KeyCode::PageUp,
+        "kNXT" => KeyCode::PageDown,
+        "kDC" => KeyCode::Delete,
+        "kIC" => KeyCode::Insert,
+        _ => return None,
+    };
+    // The number is 1 + a bit field where shift is 1, alt is 2 and control is 4
+    let bits = modifier.parse::<u8>().ok()?.checked_sub(1)?;
+    Some((
+        key_code,
+        modifiers(bits & 1 != 0, bits & 4 != 0, bits & 2 != 0),
+    ))
+}
+
+/// Creates a keyboard press followed by its release
+fn key_press(key_code: KeyCode, modifiers: ModifiersState) -> Vec<Event> {
+    vec![
+        key_event(ButtonState::Pressed, key_code, modifiers),
+        key_event(ButtonState::Released, key_code, modifiers),
+    ]
+}
+
+fn key_event(state: ButtonState, key_code: KeyCode, modifiers: ModifiersState) -> Event {
+    Event::Keyboard(keyboard::Event::Input {
+        state,
+        key_code,
+        modifiers,
+    })
+}
+
+fn modifiers(shift: bool, control: bool, alt: bool) -> ModifiersState {
+    ModifiersState {
+        shift,
+        control,
+        alt,
+        logo: false,
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::{
+        button_event, key_event, modifiers, translate_function_key, translate_key,
+        translate_key_name, translate_mouse, wheel_event, with_alt, KEY_F0, WHEEL_SCROLL_LINES,
+    };
+    use iced_native::input::{keyboard, keyboard::KeyCode, mouse, mouse::Button, ButtonState};
+    use iced_native::Event;
+    use pancurses::Input;
+
+    #[test]
+    pub fn translate_key_should_handle_characters() {
+        // Uppercase letters are typed holding shift
+        assert_eq!(
+            translate_key(Input::Character('Q')),
+            Some(vec![
+                key_event(
+                    ButtonState::Pressed,
+                    KeyCode::Q,
+                    modifiers(true, false, false)
+                ),
+                Event::Keyboard(keyboard::Event::CharacterReceived('Q')),
+                key_event(
+                    ButtonState::Released,
+                    KeyCode::Q,
+                    modifiers(true, false, false)
+                ),
+            ])
+        );
+
+        // Characters without a key of their own are only received
+        assert_eq!(
+            translate_key(Input::Character('é')),
+            Some(vec![Event::Keyboard(keyboard::Event::CharacterReceived(
+                'é'
+            ))])
+        );
+
+        // Control characters are keys, but are not received
+        assert_eq!(
+            translate_key(Input::Character('\n')),
+            Some(vec![
+                key_event(
+                    ButtonState::Pressed,
+                    KeyCode::Enter,
+                    modifiers(false, false, false)
+                ),
+                key_event(
+                    ButtonState::Released,
+                    KeyCode::Enter,
+                    modifiers(false, false, false)
+                ),
+            ])
+        );
+        assert_eq!(
+            translate_key(Input::Character('\x03')),
+            Some(vec![
+                key_event(
+                    ButtonState::Pressed,
+                    KeyCode::C,
+                    modifiers(false, true, false)
+                ),
+                key_event(
+                    ButtonState::Released,
+                    KeyCode::C,
+                    modifiers(false, true, false)
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    pub fn translate_key_should_handle_special_keys() {
+        assert_eq!(
+            translate_key(Input::KeyBTab),
+            Some(vec![
+                key_event(
+                    ButtonState::Pressed,
+                    KeyCode::Tab,
+                    modifiers(true, false, false)
+                ),
+                key_event(
+                    ButtonState::Released,
+                    KeyCode::Tab,
+                    modifiers(true, false, false)
+                ),
+            ])
+        );
+        assert_eq!(
+            translate_key(Input::KeyF14),
+            Some(vec![
+                key_event(
+                    ButtonState::Pressed,
+                    KeyCode::F2,
+                    modifiers(true, false, false)
+                ),
+                key_event(
+                    ButtonState::Released,
+                    KeyCode::F2,
+                    modifiers(true, false, false)
+                ),
+            ])
+        );
+        assert_eq!(
+            translate_key(Input::KeyEnter),
+            Some(vec![
+                key_event(
+                    ButtonState::Pressed,
+                    KeyCode::Enter,
+                    modifiers(false, false, false)
+                ),
+                key_event(
+                    ButtonState::Released,
+                    KeyCode::Enter,
+                    modifiers(false, false, false)
+                ),
+            ])
+        );
+        assert_eq!(translate_key(Input::KeyEvent), None);
+    }
+
+    #[test]
+    pub fn translate_key_should_handle_modified_function_keys() {
+        // Shift + F12 is F24, unknown to pancurses
+        assert_eq!(
+            translate_key(Input::Unknown(KEY_F0 + 24)),
+            Some(vec![
+                key_event(
+                    ButtonState::Pressed,
+                    KeyCode::F12,
+                    modifiers(true, false, false)
+                ),
+                key_event(
+                    ButtonState::Released,
+                    KeyCode::F12,
+                    modifiers(true, false, false)
+                ),
+            ])
+        );
+        assert_eq!(
+            translate_function_key(25),
+            Some((KeyCode::F1, modifiers(false, true, false)))
+        );
+        assert_eq!(
+            translate_function_key(40),
+            Some((KeyCode::F4, modifiers(true, true, false)))
+        );
+        assert_eq!(
+            translate_function_key(60),
+            Some((KeyCode::F12, modifiers(false, false, true)))
+        );
+        assert_eq!(
+            translate_function_key(63),
+            Some((KeyCode::F3, modifiers(true, false, true)))
+        );
+        assert_eq!(translate_function_key(12), None);
+        assert_eq!(translate_function_key(64), None);
+    }
+
+    #[test]
+    pub fn translate_key_name_should_handle_modifiers() {
+        assert_eq!(
+            translate_key_name("kLFT5"),
+            Some((KeyCode::Left, modifiers(false, true, false)))
+        );
+        assert_eq!(
+            translate_key_name("kDN4"),
+            Some((KeyCode::Down, modifiers(true, false, true)))
+        );
+        assert_eq!(translate_key_name("kLFT"), None);
+        assert_eq!(translate_key_name("kFOO3"), None);
+    }
+
+    #[test]
+    pub fn with_alt_should_drop_characters() {
+        let events = translate_key(Input::Character('x')).unwrap();
+        assert_eq!(
+            with_alt(events),
+            vec![
+                key_event(
+                    ButtonState::Pressed,
+                    KeyCode::X,
+                    modifiers(false, false, true)
+                ),
+                key_event(
+                    ButtonState::Released,
+                    KeyCode::X,
+                    modifiers(false, false, true)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn translate_mouse_should_handle_buttons() {
+        let moved = Event::Mouse(mouse::Event::CursorMoved { x: 4., y: 2. });
+
+        // Motion reports only move the cursor
+        assert_eq!(
+            translate_mouse(pancurses::REPORT_MOUSE_POSITION, 4, 2),
+            vec![moved]
+        );
+
+        assert_eq!(
+            translate_mouse(pancurses::BUTTON3_PRESSED, 4, 2),
+            vec![moved, button_event(ButtonState::Pressed, Button::Right)]
+        );
+
+        // A double click is made of two clicks
+        assert_eq!(
+            translate_mouse(pancurses::BUTTON2_DOUBLE_CLICKED, 4, 2),
+            vec![
+                moved,
+                button_event(ButtonState::Pressed, Button::Middle),
+                button_event(ButtonState::Released, Button::Middle),
+                button_event(ButtonState::Pressed, Button::Middle),
+                button_event(ButtonState::Released, Button::Middle),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn translate_mouse_should_handle_wheel() {
+        let moved = Event::Mouse(mouse::Event::CursorMoved { x: 0., y: 0. });
+        assert_eq!(
+            translate_mouse(pancurses::BUTTON4_PRESSED, 0, 0),
+            vec![moved, wheel_event(WHEEL_SCROLL_LINES)]
+        );
+        assert_eq!(
+            translate_mouse(pancurses::BUTTON5_PRESSED, 0, 0),
+            vec![moved, wheel_event(-WHEEL_SCROLL_LINES)]
+        );
+    }
+}
@@ -0,0 +1,91 @@
+mod curses;
+mod headless;
+mod input;
+
+pub use self::curses::PancursesBackend;
+pub use self::headless::HeadlessBackend;
+
+use crate::colors::ColorRegistryStats;
+use crate::style::Style;
+use iced_native::Event;
+use std::any::Any;
+
+/// Size of the cells in pixels, as (width, height), when the terminal does not tell it
+pub const DEFAULT_CELL_SIZE: (u16, u16) = (10, 20);
+
+/// Gives access to a value as `Any`, which allows to downcast backends to their type.
+///
+/// It is implemented for every type.
+pub trait AsAny: Any {
+    /// Gets the value as `Any`
+    fn as_any(&self) -> &dyn Any;
+
+    /// Gets the value as a mutable `Any`
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// The place where the `PancursesRenderer` puts its output and gets its events from.
+///
+/// The renderer only works with cells: every primitive is decomposed into styled characters
+/// before reaching the backend. Only the cells that changed since the previous frame are printed.
+pub trait Backend: AsAny {
+    /// Gets the size of the drawable area, as (columns, lines)
+    fn size(&self) -> (u16, u16);
+
+    /// Clears the whole drawable area
+    fn clear(&mut self);
+
+    /// Prints a character, or a grapheme cluster, at the given position.
+    /// The position is guaranteed by the renderer to be inside the drawable area.
+    ///
+    /// Wide characters cover the next cell too, which is then given an empty content:
+    /// nothing has to be printed in it.
+    fn put_char(&mut self, x: u16, y: u16, content: &str, style: Style);
+
+    /// Shows the cursor at the given position, or hides it.
+    /// The position is guaranteed by the renderer to be inside the drawable area.
+    fn set_cursor(&mut self, position: Option<(u16, u16)>);
+
+    /// Makes everything printed since the last refresh visible
+    fn refresh(&mut self);
+
+    /// Writes an escape sequence drawing an image at the given position, once the printed
+    /// characters are visible. The position is guaranteed by the renderer to be inside
+    /// the drawable area.
+    ///
+    /// Backends that cannot display images ignore it.
+    fn write_raw(&mut self, _x: u16, _y: u16, _sequence: &str) {}
+
+    /// Gets the size of the cells in pixels, as (width, height)
+    fn cell_size(&self) -> (u16, u16) {
+        DEFAULT_CELL_SIZE
+    }
+
+    /// Polls the events that happened since the last poll, if any
+    fn poll_events(&mut self) -> Option<Vec<Event>>;
+
+    /// Sets whether polling events should return immediately when there is none
+    fn set_nodelay(&mut self, nodelay: bool);
+
+    /// Returns whether characters printed before may not be displayed as they were printed
+    /// anymore, e.g. because their color pair has been reused. The renderer then prints
+    /// the whole frame again.
+    fn take_invalidated(&mut self) -> bool {
+        false
+    }
+
+    /// Gets statistics about the color pairs used by the backend, if it uses any
+    fn color_stats(&self) -> Option<ColorRegistryStats> {
+        None
+    }
+}
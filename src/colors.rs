@@ -19,33 +19,350 @@ impl PancursesColor {
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Statistics about the color pairs used by a ColorRegistry
+pub struct ColorRegistryStats {
+    /// Number of color pairs currently allocated
+    pub pairs: usize,
+    /// Number of color pairs that can be allocated
+    pub capacity: usize,
+    /// Number of color pairs initialized since the creation of the registry
+    pub allocations: u64,
+    /// Number of allocated color pairs that have been reused for another color
+    pub evictions: u64,
+}
+
+/// Allocates the pancurses color pairs, within the limit of the terminal.
+///
+/// A pair is only initialized the first time its colors are used.
+/// When every pair is allocated, the least recently used one is reused.
+///
+/// When a screen needs more pairs than the terminal has, the closest allocated pair is used
+/// for the extra colors instead, until the screen is cleared: reusing pairs would repaint it
+/// over and over.
 #[derive(Debug)]
 pub struct ColorRegistry {
-    colors: HashMap<PancursesColor, i16>,
-    idx: i16,
+    /// Pair index of the registered colors, along with the time of their last use
+    colors: HashMap<PancursesColor, (i16, u64)>,
+    /// Incremented on every use of the registry, to find the least recently used pair
+    clock: u64,
+    /// Number of color pairs that can be allocated
+    capacity: usize,
+    /// Time of the last clear of the screen: pairs used since then may be displayed
+    cleared_at: u64,
+    /// Whether the whole screen is being printed, since the last clear
+    repainting: bool,
+    /// Whether the screen has needed more pairs than the terminal has, since the last clear
+    saturated: bool,
+    /// Whether a displayed pair has been reused since the last call to `take_evicted`
+    evicted: bool,
+    /// Number of pairs initialized so far
+    allocations: u64,
+    /// Number of pairs reused so far
+    evictions: u64,
 }
 
-impl Default for ColorRegistry {
-    fn default() -> Self {
+impl ColorRegistry {
+    /// Creates a registry allocating up to the given number of color pairs, the pair 0 excluded
+    pub fn new(capacity: usize) -> Self {
         ColorRegistry {
-            colors: Default::default(),
-            idx: 1,
+            colors: HashMap::new(),
+            clock: 0,
+            capacity: capacity.min(i16::MAX as usize),
+            cleared_at: 0,
+            repainting: false,
+            saturated: false,
+            evicted: false,
+            allocations: 0,
+            evictions: 0,
         }
     }
-}
 
-impl ColorRegistry {
+    /// Creates a registry using every color pair of the terminal. Colors must have been started.
+    pub fn detect() -> Self {
+        // The pair 0 is reserved for the default colors of the terminal
+        ColorRegistry::new((pancurses::COLOR_PAIRS() - 1).max(0) as usize)
+    }
+
     /// Gets the pancurses internal index of the PancursesColor.
     /// Initializes the color if it is not in the registry.
-    pub fn get_idx(&mut self, color: PancursesColor) -> i16 {
-        let idx = *self.colors.entry(color).or_insert(self.idx);
-        pancurses::init_pair(idx, color.foreground, color.backgroud);
-        self.idx += 1;
+    pub fn get_idx(&mut self, color: PancursesColor, palette: &ColorPalette) -> i16 {
+        let (idx, is_new) = self.allocate(color, palette);
+        if is_new {
+            pancurses::init_pair(idx, color.foreground, color.backgroud);
+        }
         idx
     }
+
+    /// Gets the pair index of the PancursesColor, and whether the pair has to be initialized
+    fn allocate(&mut self, color: PancursesColor, palette: &ColorPalette) -> (i16, bool) {
+        self.clock += 1;
+        if let Some((idx, last_use)) = self.colors.get_mut(&color) {
+            *last_use = self.clock;
+            return (*idx, false);
+        }
+        if self.capacity == 0 {
+            return (0, false);
+        }
+        let idx = if self.colors.len() < self.capacity {
+            self.colors.len() as i16 + 1
+        } else {
+            let (lru, last_use) = self
+                .colors
+                .iter()
+                .min_by_key(|(_, (_, last_use))| *last_use)
+                .map(|(color, (_, last_use))| (*color, *last_use))
+                .expect("A full registry has colors");
+            let displayed = last_use > self.cleared_at;
+            // Every pair is needed by the screen being printed: reusing one of them would
+            // require printing it again, with the same issue
+            if displayed && self.repainting {
+                self.saturated = true;
+            }
+            if displayed && self.saturated {
+                return (self.closest(color, palette), false);
+            }
+            self.evicted |= displayed;
+            self.evictions += 1;
+            self.colors.remove(&lru).expect("The color is registered").0
+        };
+        self.allocations += 1;
+        self.colors.insert(color, (idx, self.clock));
+        (idx, true)
+    }
+
+    /// Gets the index of the allocated pair with the colors closest to the PancursesColor
+    fn closest(&self, color: PancursesColor, palette: &ColorPalette) -> i16 {
+        self.colors
+            .iter()
+            .min_by_key(|(allocated, (idx, _))| {
+                let distance = palette.distance(allocated.foreground, color.foreground)
+                    + palette.distance(allocated.backgroud, color.backgroud);
+                (distance, *idx)
+            })
+            .map(|(_, (idx, _))| *idx)
+            .unwrap_or(0)
+    }
+
+    /// Records that the screen has been cleared, and is printed again as a whole.
+    /// The pairs of the characters printed before are no longer displayed.
+    pub fn clear(&mut self) {
+        self.cleared_at = self.clock;
+        self.repainting = true;
+        self.saturated = false;
+    }
+
+    /// Returns whether a displayed pair has been reused since the last call, resetting the flag.
+    /// Characters printed before with the reused pair now have its new colors.
+    ///
+    /// The screen is considered printed as a whole once it is called after a clear.
+    pub fn take_evicted(&mut self) -> bool {
+        self.repainting = false;
+        std::mem::replace(&mut self.evicted, false)
+    }
+
+    /// Gets statistics about the pairs used by the registry
+    pub fn stats(&self) -> ColorRegistryStats {
+        ColorRegistryStats {
+            pairs: self.colors.len(),
+            capacity: self.capacity,
+            allocations: self.allocations,
+            evictions: self.evictions,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The colors a terminal is able to display
+pub enum ColorMode {
+    /// Only the 8 basic colors
+    Basic,
+    /// The 256 colors palette of xterm
+    Palette256,
+    /// Colors defined on the fly using `init_color`, on truecolor terminals allowing it
+    Custom,
+}
+
+impl ColorMode {
+    /// Detects the colors supported by the terminal. Colors must have been started.
+    pub fn detect() -> Self {
+        ColorMode::from_capabilities(
+            pancurses::COLORS(),
+            pancurses::can_change_color(),
+            std::env::var("COLORTERM").ok().as_deref(),
+        )
+    }
+
+    /// Chooses the color mode matching the given terminal capabilities
+    pub fn from_capabilities(colors: i32, can_change_color: bool, colorterm: Option<&str>) -> Self {
+        let truecolor = colorterm == Some("truecolor") || colorterm == Some("24bit");
+        if colors < 256 {
+            ColorMode::Basic
+        } else if can_change_color && truecolor {
+            ColorMode::Custom
+        } else {
+            ColorMode::Palette256
+        }
+    }
+}
+
+/// Translates iced_native Colors into pancurses colors, according to the color mode
+#[derive(Debug)]
+pub struct ColorPalette {
+    /// Colors supported by the terminal
+    mode: ColorMode,
+    /// Colors defined using `init_color`, by RGB components
+    custom_colors: HashMap<[u8; 3], i16>,
+    /// Number of colors of the terminal, bounding the custom colors
+    colors: i32,
+}
+
+impl ColorPalette {
+    /// The first 16 colors are left untouched, as they are used by other programs and the shell
+    const FIRST_CUSTOM_COLOR: i16 = 16;
+
+    /// Creates a palette for a terminal of the given color mode and number of colors
+    pub fn new(mode: ColorMode, colors: i32) -> Self {
+        ColorPalette {
+            mode,
+            custom_colors: HashMap::new(),
+            colors,
+        }
+    }
+
+    /// Creates a palette matching the capabilities of the terminal. Colors must have been started.
+    pub fn detect() -> Self {
+        ColorPalette::new(ColorMode::detect(), pancurses::COLORS())
+    }
+
+    /// Gets the pancurses color displaying the iced_native Color
+    pub fn get_color(&mut self, color: Color) -> i16 {
+        match self.mode {
+            ColorMode::Basic => get_closest_color(color),
+            ColorMode::Palette256 => get_closest_palette_color(color),
+            ColorMode::Custom => self.get_custom_color(color),
+        }
+    }
+
+    /// Gets the square of the distance between the RGB values of two pancurses colors.
+    /// The default color of the terminal, -1, is only close to itself.
+    fn distance(&self, a: i16, b: i16) -> u32 {
+        match (self.rgb(a), self.rgb(b)) {
+            (Some(a), Some(b)) => rgb_distance(a, b),
+            _ if a == b => 0,
+            _ => rgb_distance([0; 3], [255; 3]),
+        }
+    }
+
+    /// Gets the RGB value of a pancurses color, if it is not the default color of the terminal.
+    /// The basic colors are approximated, as their exact value depends on the terminal theme.
+    fn rgb(&self, idx: i16) -> Option<[u8; 3]> {
+        let custom = match self.mode {
+            ColorMode::Custom => self
+                .custom_colors
+                .iter()
+                .find(|(_, custom_idx)| **custom_idx == idx)
+                .map(|(rgb, _)| *rgb),
+            _ => None,
+        };
+        match idx {
+            _ if custom.is_some() => custom,
+            // The basic colors have a bit per component, their bright version being the same
+            0..=15 => {
+                let component = |bit: i16| if idx & bit != 0 { 255 } else { 0 };
+                Some([component(1), component(2), component(4)])
+            }
+            16..=231 => {
+                let cube = idx as usize - 16;
+                Some([
+                    CUBE_LEVELS[cube / 36],
+                    CUBE_LEVELS[cube / 6 % 6],
+                    CUBE_LEVELS[cube % 6],
+                ])
+            }
+            232..=255 => Some([(8 + 10 * (idx - 232)) as u8; 3]),
+            _ => None,
+        }
+    }
+
+    /// Gets the custom color defined for the iced_native Color, defining it if needed.
+    /// Once every color of the terminal is defined, the closest defined color is used.
+    fn get_custom_color(&mut self, color: Color) -> i16 {
+        let rgb = to_rgb(color);
+        if let Some(idx) = self.custom_colors.get(&rgb) {
+            return *idx;
+        }
+        let idx = Self::FIRST_CUSTOM_COLOR + self.custom_colors.len() as i16;
+        if (idx as i32) < self.colors {
+            // Curses color components range from 0 to 1000
+            let [r, g, b] = rgb;
+            let component = |c: u8| (c as i32 * 1000 / 255) as i16;
+            pancurses::init_color(idx, component(r), component(g), component(b));
+            self.custom_colors.insert(rgb, idx);
+            idx
+        } else {
+            self.custom_colors
+                .iter()
+                .min_by_key(|(defined, _)| rgb_distance(**defined, rgb))
+                .map(|(_, idx)| *idx)
+                .unwrap_or_else(|| get_closest_color(color))
+        }
+    }
+}
+
+/// Gets the 8 bit RGB components of an iced_native Color
+fn to_rgb(color: Color) -> [u8; 3] {
+    [
+        (color.r * 255.).round() as u8,
+        (color.g * 255.).round() as u8,
+        (color.b * 255.).round() as u8,
+    ]
 }
 
-/// Gets the closest pancurses-supported color matching the iced_native Color
+/// Gets the square of the distance between two RGB colors
+fn rgb_distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| (*a as i32 - *b as i32).pow(2) as u32)
+        .sum()
+}
+
+/// Levels of each component in the 6x6x6 color cube of the 256 colors palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Gets the closest color of the 256 colors palette matching the iced_native Color.
+///
+/// The 16 first colors are not considered, as their exact value depends on the terminal theme:
+/// the color is matched against the color cube and the grayscale ramp.
+pub(crate) fn get_closest_palette_color(source: Color) -> i16 {
+    let rgb = to_rgb(source);
+    let closest_level = |c: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|idx| (CUBE_LEVELS[*idx] as i32 - c as i32).abs())
+            .unwrap_or(0)
+    };
+    let [r, g, b] = [
+        closest_level(rgb[0]),
+        closest_level(rgb[1]),
+        closest_level(rgb[2]),
+    ];
+    let cube_idx = 16 + 36 * r + 6 * g + b;
+    let cube_rgb = [CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]];
+
+    // The grayscale ramp goes from 8 to 238, by steps of 10
+    let average = rgb.iter().map(|c| *c as i32).sum::<i32>() / 3;
+    let gray = ((average - 8 + 5) / 10).clamp(0, 23);
+    let gray_level = (8 + 10 * gray) as u8;
+    let gray_idx = 232 + gray as usize;
+
+    if rgb_distance([gray_level; 3], rgb) < rgb_distance(cube_rgb, rgb) {
+        gray_idx as i16
+    } else {
+        cube_idx as i16
+    }
+}
+
+/// Gets the closest of the 8 basic pancurses colors matching the iced_native Color
 pub(crate) fn get_closest_color(source: Color) -> i16 {
     let [r_src, g_src, b_src, _] = source.into_linear();
     vec![
@@ -121,3 +438,111 @@ pub(crate) fn get_closest_color(source: Color) -> i16 {
     .unwrap_or((0., pancurses::COLOR_WHITE))
     .1
 }
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::{
+        get_closest_color, get_closest_palette_color, ColorMode, ColorPalette, ColorRegistry,
+        ColorRegistryStats, PancursesColor,
+    };
+    use iced_native::Color;
+
+    #[test]
+    pub fn color_mode_should_match_capabilities() {
+        assert_eq!(
+            ColorMode::from_capabilities(8, true, Some("truecolor")),
+            ColorMode::Basic
+        );
+        assert_eq!(
+            ColorMode::from_capabilities(256, false, None),
+            ColorMode::Palette256
+        );
+        assert_eq!(
+            ColorMode::from_capabilities(256, true, None),
+            ColorMode::Palette256
+        );
+        assert_eq!(
+            ColorMode::from_capabilities(256, true, Some("24bit")),
+            ColorMode::Custom
+        );
+    }
+
+    #[test]
+    pub fn palette_should_match_closest_color() {
+        // Pure colors are part of the color cube
+        assert_eq!(get_closest_palette_color(Color::BLACK), 16);
+        assert_eq!(get_closest_palette_color(Color::WHITE), 231);
+        assert_eq!(get_closest_palette_color(Color::from_rgb(1., 0., 0.)), 196);
+        // Grays are matched in the grayscale ramp: 0.5 is 128, close to the 128 gray (244)
+        assert_eq!(
+            get_closest_palette_color(Color::from_rgb(0.5, 0.5, 0.5)),
+            244
+        );
+        // Shades are kept, while basic colors only have the closest hue
+        let orange = Color::from_rgb(1., 0.5, 0.);
+        assert_eq!(get_closest_palette_color(orange), 208);
+        let mut palette = ColorPalette::new(ColorMode::Basic, 8);
+        assert_eq!(palette.get_color(orange), get_closest_color(orange));
+    }
+
+    #[test]
+    pub fn color_registry_should_reuse_pairs() {
+        let mut registry = ColorRegistry::new(2);
+        let palette = ColorPalette::new(ColorMode::Palette256, 256);
+        let (a, b, c) = (
+            PancursesColor::new(1, -1),
+            PancursesColor::new(2, -1),
+            PancursesColor::new(3, -1),
+        );
+        // Colors are only allocated once
+        assert_eq!(registry.allocate(a, &palette), (1, true));
+        assert_eq!(registry.allocate(a, &palette), (1, false));
+        assert_eq!(registry.allocate(b, &palette), (2, true));
+        assert!(!registry.take_evicted());
+
+        // Once full, the least recently used pair is reused: b, as a has just been used
+        registry.allocate(a, &palette);
+        assert_eq!(registry.allocate(c, &palette), (2, true));
+        assert!(registry.take_evicted());
+        assert!(!registry.take_evicted());
+        assert_eq!(registry.allocate(a, &palette), (1, false));
+        assert_eq!(
+            registry.stats(),
+            ColorRegistryStats {
+                pairs: 2,
+                capacity: 2,
+                allocations: 3,
+                evictions: 1,
+            }
+        );
+    }
+
+    #[test]
+    pub fn color_registry_should_use_closest_pair_when_saturated() {
+        let mut registry = ColorRegistry::new(2);
+        let palette = ColorPalette::new(ColorMode::Palette256, 256);
+        let (red, blue, dark_red, cyan) = (
+            PancursesColor::new(196, -1),
+            PancursesColor::new(21, -1),
+            PancursesColor::new(160, -1),
+            PancursesColor::new(51, -1),
+        );
+        // The screen needs more pairs than there are: the closest one is used for the extra color
+        registry.clear();
+        assert_eq!(registry.allocate(red, &palette), (1, true));
+        assert_eq!(registry.allocate(blue, &palette), (2, true));
+        assert_eq!(registry.allocate(dark_red, &palette), (1, false));
+        assert!(!registry.take_evicted());
+
+        // ... and for the colors printed afterwards, as the screen would have to be printed again
+        assert_eq!(registry.allocate(cyan, &palette), (2, false));
+        assert!(!registry.take_evicted());
+
+        // Once cleared, pairs that are not displayed anymore are reused
+        registry.clear();
+        assert_eq!(registry.allocate(cyan, &palette), (1, true));
+        assert!(!registry.take_evicted());
+        assert_eq!(registry.stats().evictions, 1);
+    }
+}
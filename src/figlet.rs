@@ -0,0 +1,309 @@
+//! FIGlet fonts, drawing text as ASCII art.
+//!
+//! Fonts are read from `.flf` files, whose format is described in the
+//! [FIGlet documentation](http://www.jave.de/figlet/figfont.html).
+//! Horizontal smushing is not supported: fonts asking for it are laid out by fitting their
+//! characters next to each other instead.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// Font bundled with the crate, with small glyphs drawn using half block characters
+const BLOCKS_FONT: &str = include_str!("../fonts/blocks.flf");
+
+/// Codes of the german characters, required after the ASCII ones
+const DEUTSCH_CODES: [u32; 7] = [196, 214, 220, 228, 246, 252, 223];
+
+#[derive(Debug)]
+/// An error happening while loading a FIGlet font
+pub enum FontError {
+    /// The font file could not be read
+    Io(std::io::Error),
+    /// The header of the font is invalid
+    Header,
+    /// The character of the given code is missing, or incomplete
+    Character(i64),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontError::Io(error) => write!(f, "could not read the font: {}", error),
+            FontError::Header => write!(f, "invalid font header"),
+            FontError::Character(code) => write!(f, "missing character {} in font", code),
+        }
+    }
+}
+
+impl std::error::Error for FontError {}
+
+impl From<std::io::Error> for FontError {
+    fn from(error: std::io::Error) -> Self {
+        FontError::Io(error)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How the characters are put next to each other
+enum Layout {
+    /// Characters are put next to each other, with their blanks
+    FullWidth,
+    /// Characters are moved closer until they touch
+    Fitting,
+}
+
+/// A FIGlet font
+#[derive(Debug, Clone)]
+pub struct FigletFont {
+    /// Number of lines of every character
+    height: usize,
+    /// Character standing for spaces that are never removed when fitting characters
+    hardblank: char,
+    /// Default layout of the font
+    layout: Layout,
+    /// Lines of each character of the font
+    chars: HashMap<char, Vec<Vec<char>>>,
+}
+
+impl FigletFont {
+    /// Gets the font bundled with the crate, 3 lines tall
+    pub fn blocks() -> Self {
+        FigletFont::parse(BLOCKS_FONT).expect("The bundled font is valid")
+    }
+
+    /// Loads a font from a `.flf` file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, FontError> {
+        FigletFont::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parses the content of a `.flf` file
+    pub fn parse(content: &str) -> Result<Self, FontError> {
+        let mut lines = content.lines();
+        let header = lines.next().ok_or(FontError::Header)?;
+        if !header.starts_with("flf2a") {
+            return Err(FontError::Header);
+        }
+        let hardblank = header.chars().nth(5).ok_or(FontError::Header)?;
+        let params = header[5 + hardblank.len_utf8()..]
+            .split_whitespace()
+            .map(|param| param.parse::<i32>().map_err(|_| FontError::Header))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Height, baseline, max length, old layout and comment lines are mandatory
+        if params.len() < 5 || params[0] < 1 {
+            return Err(FontError::Header);
+        }
+        let height = params[0] as usize;
+        let old_layout = params[3];
+        let layout = match params.get(5) {
+            // Horizontal fitting or smushing bits of the full layout
+            Some(full_layout) if full_layout & (64 | 128) != 0 => Layout::Fitting,
+            Some(_) => Layout::FullWidth,
+            None if old_layout < 0 => Layout::FullWidth,
+            None => Layout::Fitting,
+        };
+        for _ in 0..params[4] {
+            lines.next();
+        }
+
+        let mut font = FigletFont {
+            height,
+            hardblank,
+            layout,
+            chars: HashMap::new(),
+        };
+        for code in 32..127 {
+            let glyph = read_glyph(&mut lines, height).ok_or(FontError::Character(code.into()))?;
+            font.insert(code, glyph);
+        }
+        // Old fonts may lack the german characters, and anything that follows
+        for code in DEUTSCH_CODES.iter() {
+            match read_glyph(&mut lines, height) {
+                Some(glyph) => font.insert(*code, glyph),
+                None => return Ok(font),
+            }
+        }
+        // Other characters are preceded by their code
+        while let Some(line) = lines.next() {
+            let tag = match line.split_whitespace().next() {
+                Some(tag) => tag,
+                None => continue,
+            };
+            let code = parse_code(tag).ok_or(FontError::Header)?;
+            let glyph = read_glyph(&mut lines, height).ok_or(FontError::Character(code))?;
+            // Negative codes are reserved for translation tables, which are not supported
+            if code >= 0 {
+                font.insert(code as u32, glyph);
+            }
+        }
+        Ok(font)
+    }
+
+    /// Gets the number of lines of the text drawn for a line of text
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Draws a text, returning the lines of the drawing.
+    /// Every line of the text is drawn under the previous one.
+    /// Characters that are not in the font are left out.
+    pub fn render(&self, text: &str) -> Vec<String> {
+        text.lines()
+            .flat_map(|line| self.render_line(line))
+            .collect()
+    }
+
+    /// Draws a single line of text
+    fn render_line(&self, text: &str) -> Vec<String> {
+        let mut rows: Vec<Vec<char>> = vec![Vec::new(); self.height];
+        for glyph in text.chars().filter_map(|c| self.chars.get(&c)) {
+            let overlap = match self.layout {
+                Layout::FullWidth => 0,
+                Layout::Fitting => self.overlap(&rows, glyph),
+            };
+            for (row, glyph_row) in rows.iter_mut().zip(glyph.iter()) {
+                let start = row.len() - overlap;
+                for (idx, c) in glyph_row.iter().enumerate() {
+                    match row.get_mut(start + idx) {
+                        // Overlapping columns are blank on one side at least
+                        Some(existing) if *c != ' ' => *existing = *c,
+                        Some(_) => {}
+                        None => row.push(*c),
+                    }
+                }
+            }
+        }
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        rows.into_iter()
+            .map(|row| {
+                let hardblank = self.hardblank;
+                let mut line: String = row
+                    .into_iter()
+                    .map(|c| if c == hardblank { ' ' } else { c })
+                    .collect();
+                line.extend((line.chars().count()..width).map(|_| ' '));
+                line
+            })
+            .collect()
+    }
+
+    /// Gets the number of columns by which a character can be moved over the drawn text,
+    /// without any of their visible parts overlapping
+    fn overlap(&self, rows: &[Vec<char>], glyph: &[Vec<char>]) -> usize {
+        let glyph_width = glyph.iter().map(|row| row.len()).max().unwrap_or(0);
+        rows.iter()
+            .zip(glyph.iter())
+            .map(|(row, glyph_row)| {
+                let trailing = row.iter().rev().take_while(|c| **c == ' ').count();
+                let leading = glyph_row.iter().take_while(|c| **c == ' ').count();
+                // Blank rows of the character do not constrain the overlap
+                if leading == glyph_row.len() {
+                    glyph_width
+                } else {
+                    trailing + leading
+                }
+            })
+            .min()
+            .unwrap_or(0)
+            .min(glyph_width)
+            .min(rows.iter().map(|row| row.len()).min().unwrap_or(0))
+    }
+
+    /// Adds a character to the font, ignoring invalid codes
+    fn insert(&mut self, code: u32, glyph: Vec<Vec<char>>) {
+        if let Some(c) = std::char::from_u32(code) {
+            self.chars.insert(c, glyph);
+        }
+    }
+}
+
+/// Reads the lines of a character, removing their end marks
+fn read_glyph<'a>(
+    lines: &mut impl Iterator<Item = &'a str>,
+    height: usize,
+) -> Option<Vec<Vec<char>>> {
+    (0..height)
+        .map(|_| {
+            let line = lines.next()?.trim_end();
+            let endmark = line.chars().last()?;
+            Some(line.trim_end_matches(endmark).chars().collect())
+        })
+        .collect()
+}
+
+/// Parses the code of a character, written in decimal, hexadecimal or octal
+fn parse_code(tag: &str) -> Option<i64> {
+    let (negative, tag) = match tag.strip_prefix('-') {
+        Some(tag) => (true, tag),
+        None => (false, tag),
+    };
+    let code = if let Some(hex) = tag.strip_prefix("0x").or_else(|| tag.strip_prefix("0X")) {
+        i64::from_str_radix(hex, 16).ok()?
+    } else if tag.len() > 1 && tag.starts_with('0') {
+        i64::from_str_radix(&tag[1..], 8).ok()?
+    } else {
+        tag.parse().ok()?
+    };
+    Some(if negative { -code } else { code })
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::{FigletFont, FontError};
+
+    /// A font with two characters, and every other one empty
+    fn test_font(old_layout: i32) -> String {
+        let mut font = format!("flf2a$ 2 2 4 {} 1\nA test font\n", old_layout);
+        for c in 32..127 {
+            match c as u8 {
+                b'I' => font.push_str(" |$@\n |$@@\n"),
+                b'L' => font.push_str("| @\n|_@@\n"),
+                _ => font.push_str("@\n@@\n"),
+            }
+        }
+        font
+    }
+
+    #[test]
+    pub fn figlet_font_should_render_text() {
+        let font = FigletFont::parse(&test_font(-1)).unwrap();
+        // Characters are put next to each other, hardblanks becoming spaces
+        assert_eq!(font.render("LI"), vec!["|  | ", "|_ | "]);
+
+        // When fitting, characters touch, without overlapping the hardblank of the I
+        let font = FigletFont::parse(&test_font(0)).unwrap();
+        assert_eq!(font.render("LI"), vec!["| | ", "|_| "]);
+        assert_eq!(font.render("LIL"), vec!["| | | ", "|_| |_"]);
+        assert_eq!(font.render("I\nI"), vec![" | ", " | ", " | ", " | "]);
+    }
+
+    #[test]
+    pub fn figlet_font_should_reject_invalid_fonts() {
+        assert!(matches!(
+            FigletFont::parse("not a font"),
+            Err(FontError::Header)
+        ));
+        assert!(matches!(
+            FigletFont::parse("flf2a$ 2 2 4 0 0\n|@\n|@@\n"),
+            Err(FontError::Character(33))
+        ));
+
+        // Characters preceded by their code report it when they are incomplete
+        let mut font = test_font(0);
+        // ... after the german characters
+        (0..7).for_each(|_| font.push_str("@\n@@\n"));
+        font.push_str("0x263A smiley\n:)@\n");
+        assert!(matches!(
+            FigletFont::parse(&font),
+            Err(FontError::Character(0x263A))
+        ));
+    }
+
+    #[test]
+    pub fn bundled_font_should_load() {
+        let font = FigletFont::blocks();
+        assert_eq!(font.height(), 3);
+        assert_eq!(font.render("Hi"), vec!["█ █ ▀█▀ ", "█▀█  █  ", "▀ ▀ ▀▀▀ "]);
+    }
+}
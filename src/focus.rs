@@ -0,0 +1,719 @@
+//! Keyboard focus navigation.
+//!
+//! Widget renderers register the bounds of the widgets that can be focused while they are drawn.
+//! Keyboard events are then translated, before reaching iced: Tab and Shift-Tab (or the arrows)
+//! move the focus across the registered widgets, and Enter or Space activate the focused widget
+//! by clicking on it.
+//!
+//! Widgets inside of scrollables are registered where they are shown, once scrolled: only their
+//! visible part can be clicked, and moving the focus to a widget scrolled out of view scrolls it
+//! back into view.
+//!
+//! Scrollables are scrolled with the arrows, Page Up / Page Down and Home / End when they are
+//! focused, or when the mouse is over them and no other widget is focused.
+//! The focused slider is moved by one column with the left and right arrows, and to the ends of
+//! its range with Home / End.
+
+use crate::renderer::intersection;
+use iced_native::input::{keyboard, keyboard::KeyCode, mouse, ButtonState};
+use iced_native::{Color, Event, Point, Rectangle};
+
+/// Color used to highlight the focused widget
+pub const FOCUS_COLOR: Color = Color {
+    r: 1.,
+    g: 1.,
+    b: 0.,
+    a: 1.,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The kinds of widgets that can be focused
+pub enum FocusKind {
+    Button,
+    Checkbox,
+    Radio,
+    Slider,
+    TextInput,
+    Scrollable,
+}
+
+#[derive(Debug, Clone, Copy)]
+/// A widget that can be focused
+struct Focusable {
+    kind: FocusKind,
+    /// Bounds of the widget in the layout, which identify it from a frame to the next
+    layout_bounds: Rectangle,
+    /// Bounds of the widget on the screen, once moved by the scrollables containing it
+    bounds: Rectangle,
+    /// Part of the widget shown on the screen, inside of the scrollables containing it
+    visible: Rectangle,
+    /// Area showing the content of the innermost scrollable containing the widget, if any
+    viewport: Option<Rectangle>,
+    /// Height of the content of the widget, which is larger than its bounds when it scrolls
+    content_height: f32,
+    /// Position of the value of a slider in its range, from 0 to 1
+    value: f32,
+}
+
+impl Focusable {
+    /// Creates a widget that is not inside of a scrollable
+    fn new(bounds: Rectangle, kind: FocusKind) -> Self {
+        Focusable {
+            kind,
+            layout_bounds: bounds,
+            bounds,
+            visible: bounds,
+            viewport: None,
+            content_height: bounds.height,
+            value: 0.,
+        }
+    }
+
+    /// Returns whether both widgets are the same one, drawn in different frames.
+    /// Widgets are the same when they are of the same kind, at the same place in the layout.
+    fn is_same_widget(&self, other: &Focusable) -> bool {
+        self.kind == other.kind
+            && self.layout_bounds.x == other.layout_bounds.x
+            && self.layout_bounds.y == other.layout_bounds.y
+    }
+
+    /// Returns whether some of the widget is shown on the screen
+    fn is_visible(&self) -> bool {
+        self.visible.width > 0. && self.visible.height > 0.
+    }
+
+    /// Moves the widget up by the offset of the scrollable containing it,
+    /// hiding what gets out of the viewport of the scrollable
+    fn scroll(&mut self, offset: f32, viewport: Rectangle) {
+        self.bounds.y -= offset;
+        self.visible.y -= offset;
+        self.visible = intersection(self.visible, viewport);
+        self.viewport = Some(match self.viewport {
+            Some(mut inner) => {
+                inner.y -= offset;
+                intersection(inner, viewport)
+            }
+            None => viewport,
+        });
+    }
+
+    /// Gets the position at which the widget is clicked: the middle of its visible part
+    fn center(&self) -> Point {
+        Point::new(
+            self.visible.x + (self.visible.width / 2.).floor(),
+            self.visible.y + (self.visible.height / 2.).floor(),
+        )
+    }
+}
+
+/// Returns whether a rectangle is entirely inside of another one
+fn contains(outer: Rectangle, inner: Rectangle) -> bool {
+    inner.x >= outer.x
+        && inner.y >= outer.y
+        && inner.x + inner.width <= outer.x + outer.width
+        && inner.y + inner.height <= outer.y + outer.height
+}
+
+/// The widgets that can be focused, in drawing order, and the one that currently is
+#[derive(Debug, Default)]
+pub struct FocusRing {
+    /// Widgets drawn during the last frame
+    focusables: Vec<Focusable>,
+    /// Widgets registered during the frame being drawn
+    drawing: Vec<Focusable>,
+    /// Index of the focused widget among the widgets drawn during the last frame, if any
+    focused: Option<usize>,
+    /// Key whose press has been handled, to ignore until it is released
+    swallowed_key: Option<KeyCode>,
+    /// Last known position of the mouse cursor
+    cursor_position: Point,
+}
+
+impl FocusRing {
+    /// Registers a widget being drawn, returning whether it is focused
+    pub fn register(&mut self, bounds: Rectangle, kind: FocusKind) -> bool {
+        self.push(Focusable::new(bounds, kind))
+    }
+
+    /// Registers a slider being drawn, with the position of its value in its range,
+    /// returning whether it is focused
+    pub fn register_slider(&mut self, bounds: Rectangle, value: f32) -> bool {
+        self.push(Focusable {
+            value,
+            ..Focusable::new(bounds, FocusKind::Slider)
+        })
+    }
+
+    /// Registers a scrollable being drawn, returning whether it is focused.
+    ///
+    /// Its content is scrolled by the offset and shown within its bounds: the widgets of the
+    /// content are moved and clipped accordingly.
+    pub fn register_scrollable(
+        &mut self,
+        bounds: Rectangle,
+        content_bounds: Rectangle,
+        offset: f32,
+    ) -> bool {
+        // The content is drawn just before the scrollable: its widgets are the last registered
+        // ones, inside of the content in the layout
+        self.drawing
+            .iter_mut()
+            .rev()
+            .take_while(|focusable| contains(content_bounds, focusable.layout_bounds))
+            .for_each(|focusable| focusable.scroll(offset, bounds));
+        self.push(Focusable {
+            content_height: content_bounds.height,
+            ..Focusable::new(bounds, FocusKind::Scrollable)
+        })
+    }
+
+    /// Gets the focused widget, if any
+    fn focused_widget(&self) -> Option<Focusable> {
+        self.focused
+            .and_then(|idx| self.focusables.get(idx))
+            .copied()
+    }
+
+    /// Adds a widget to the ones of the frame being drawn, returning whether it is focused
+    fn push(&mut self, focusable: Focusable) -> bool {
+        let is_focused = match self.focused_widget() {
+            Some(focused) => focused.is_same_widget(&focusable),
+            None => false,
+        };
+        self.drawing.push(focusable);
+        is_focused
+    }
+
+    /// Ends the frame being drawn: the registered widgets become the ones to navigate.
+    /// The focus stays on the same widget, and is removed if it is not drawn anymore.
+    pub fn end_frame(&mut self) {
+        let focused = self.focused_widget();
+        self.focusables = std::mem::take(&mut self.drawing);
+        let focusables = &self.focusables;
+        self.focused = focused.and_then(|focused| {
+            focusables
+                .iter()
+                .position(|focusable| focusable.is_same_widget(&focused))
+        });
+    }
+
+    /// Gets the kind of the focused widget, if any
+    pub fn focused_kind(&self) -> Option<FocusKind> {
+        self.focused_widget().map(|focusable| focusable.kind)
+    }
+
+    /// Translates the events that are related to the focus, leaving the others untouched
+    pub fn handle(&mut self, events: Vec<Event>) -> Vec<Event> {
+        let mut output = Vec::new();
+        for event in events {
+            match event {
+                Event::Mouse(mouse::Event::CursorMoved { x, y }) => {
+                    self.cursor_position = Point::new(x, y);
+                    output.push(event);
+                }
+                // Clicking on a widget focuses it, clicking elsewhere removes the focus
+                Event::Mouse(mouse::Event::Input {
+                    state: ButtonState::Pressed,
+                    button: mouse::Button::Left,
+                }) => {
+                    let cursor_position = self.cursor_position;
+                    self.focused = self
+                        .focusables
+                        .iter()
+                        .position(|focusable| focusable.visible.contains(cursor_position));
+                    output.push(event);
+                }
+                Event::Keyboard(keyboard::Event::Input {
+                    state: ButtonState::Pressed,
+                    key_code,
+                    modifiers,
+                }) => match self.handle_key(key_code, modifiers) {
+                    Some(mut events) => {
+                        self.swallowed_key = Some(key_code);
+                        output.append(&mut events);
+                    }
+                    None => output.push(event),
+                },
+                Event::Keyboard(keyboard::Event::Input {
+                    state: ButtonState::Released,
+                    key_code,
+                    ..
+                }) if self.swallowed_key == Some(key_code) => {
+                    self.swallowed_key = None;
+                }
+                Event::Keyboard(keyboard::Event::CharacterReceived(_))
+                    if self.swallowed_key.is_some() => {}
+                _ => output.push(event),
+            }
+        }
+        output
+    }
+
+    /// Handles the press of a key, returning the events replacing it if it is related to the focus
+    fn handle_key(
+        &mut self,
+        key_code: KeyCode,
+        modifiers: keyboard::ModifiersState,
+    ) -> Option<Vec<Event>> {
+        // Arrows are left to text inputs and sliders, which use them to move their cursor or value
+        let (arrows_navigate, left_right_navigate, activable) = match self.focused_kind() {
+            Some(FocusKind::Button) | Some(FocusKind::Checkbox) | Some(FocusKind::Radio) => {
+                (true, true, true)
+            }
+            Some(FocusKind::Slider) => (true, false, false),
+            Some(FocusKind::TextInput) | Some(FocusKind::Scrollable) | None => {
+                (false, false, false)
+            }
+        };
+        if let Some(events) = self.scroll(key_code) {
+            return Some(events);
+        }
+        if let Some(events) = self.slide(key_code) {
+            return Some(events);
+        }
+        match key_code {
+            KeyCode::Tab if modifiers.shift => self.move_focus(-1),
+            KeyCode::Tab => self.move_focus(1),
+            KeyCode::Up if arrows_navigate => self.move_focus(-1),
+            KeyCode::Down if arrows_navigate => self.move_focus(1),
+            KeyCode::Left if left_right_navigate => self.move_focus(-1),
+            KeyCode::Right if left_right_navigate => self.move_focus(1),
+            KeyCode::Enter | KeyCode::NumpadEnter | KeyCode::Space if activable => {
+                let focused = self.focused_widget().filter(Focusable::is_visible)?;
+                Some(self.click(focused.center()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Scrolls the focused scrollable, or the innermost one under the mouse when no widget is
+    /// focused, if the key is a scrolling one.
+    /// The mouse is moved over the scrollable to scroll it with the wheel, and then moved back.
+    fn scroll(&mut self, key_code: KeyCode) -> Option<Vec<Event>> {
+        let cursor_position = self.cursor_position;
+        let scrollable = match self.focused {
+            Some(idx) => Some(self.focusables[idx]).filter(|f| f.kind == FocusKind::Scrollable),
+            // Scrollables are registered after their content: the first one is the innermost
+            None => self.focusables.iter().copied().find(|focusable| {
+                focusable.kind == FocusKind::Scrollable
+                    && focusable.visible.contains(cursor_position)
+            }),
+        }
+        .filter(Focusable::is_visible)?;
+        let page = (scrollable.bounds.height - 1.).max(1.);
+        // Scrolling by a positive amount moves the content down, showing its top
+        let delta = match key_code {
+            KeyCode::Up => 1.,
+            KeyCode::Down => -1.,
+            KeyCode::PageUp => page,
+            KeyCode::PageDown => -page,
+            KeyCode::Home => scrollable.content_height,
+            KeyCode::End => -scrollable.content_height,
+            _ => return None,
+        };
+        Some(self.wheel(scrollable.center(), delta))
+    }
+
+    /// Creates the events scrolling the scrollable at the given position by a number of lines,
+    /// with the mouse wheel.
+    /// The mouse is moved over the scrollable to scroll it, and then moved back.
+    fn wheel(&self, position: Point, delta: f32) -> Vec<Event> {
+        vec![
+            Event::Mouse(mouse::Event::CursorMoved {
+                x: position.x,
+                y: position.y,
+            }),
+            Event::Mouse(mouse::Event::WheelScrolled {
+                delta: mouse::ScrollDelta::Pixels { x: 0., y: delta },
+            }),
+            Event::Mouse(mouse::Event::CursorMoved {
+                x: self.cursor_position.x,
+                y: self.cursor_position.y,
+            }),
+        ]
+    }
+
+    /// Scrolls the innermost scrollable containing a widget, if the widget is not entirely
+    /// shown in it. Returns the events scrolling it, and the number of lines the widget is
+    /// moved down by.
+    fn reveal(&self, focusable: Focusable) -> (Vec<Event>, f32) {
+        let viewport = match focusable.viewport {
+            Some(viewport) => viewport,
+            None => return (vec![], 0.),
+        };
+        let top = focusable.bounds.y - viewport.y;
+        let bottom = focusable.bounds.y + focusable.bounds.height - viewport.y - viewport.height;
+        // Widgets taller than the viewport are scrolled to their top
+        let delta = if top < 0. {
+            -top
+        } else if bottom > 0. {
+            -bottom.min(top)
+        } else {
+            0.
+        };
+        if delta == 0. {
+            return (vec![], 0.);
+        }
+        let center = Point::new(
+            viewport.x + (viewport.width / 2.).floor(),
+            viewport.y + (viewport.height / 2.).floor(),
+        );
+        (self.wheel(center, delta), delta)
+    }
+
+    /// Moves the value of the focused slider by one column with the left and right arrows,
+    /// or to an end of its range with Home / End, by clicking on the slider.
+    ///
+    /// Iced sets the value to the position of the click in the bounds of the slider: clicking on
+    /// the column `n` of a slider `w` columns wide sets it to the `n / w` of its range.
+    fn slide(&mut self, key_code: KeyCode) -> Option<Vec<Event>> {
+        let slider = self
+            .focused_widget()
+            .filter(|f| f.kind == FocusKind::Slider && f.is_visible())?;
+        let bounds = slider.bounds;
+        let width = bounds.width.floor().max(1.);
+        // Rounding errors of the value are tolerated, as when drawing the handle
+        let column = slider.value * width;
+        let target = match key_code {
+            KeyCode::Left => (column - 0.001).ceil() - 1.,
+            KeyCode::Right => (column + 0.001).floor() + 1.,
+            KeyCode::Home => 0.,
+            KeyCode::End => width,
+            _ => return None,
+        };
+        Some(self.click(Point::new(
+            bounds.x + target.clamp(0., width),
+            slider.center().y,
+        )))
+    }
+
+    /// Moves the focus by the given number of widgets, wrapping around.
+    /// Nothing is done when no widget can be focused, leaving the key to the application.
+    fn move_focus(&mut self, step: isize) -> Option<Vec<Event>> {
+        let count = self.focusables.len();
+        if count == 0 {
+            return None;
+        }
+        let mut events = vec![];
+        // Text inputs have a focus of their own, which is removed by clicking elsewhere
+        if self.focused_kind() == Some(FocusKind::TextInput) {
+            events.append(&mut self.click(Point::new(-1., -1.)));
+        }
+        let next = match self.focused {
+            Some(idx) => (idx as isize + step).rem_euclid(count as isize) as usize,
+            None if step > 0 => 0,
+            None => count - 1,
+        };
+        self.focused = Some(next);
+        // Widgets scrolled out of view are scrolled back into it
+        let focusable = self.focusables[next];
+        let (mut scroll, moved) = self.reveal(focusable);
+        events.append(&mut scroll);
+        // Text inputs are given the focus by clicking on them, where they have been scrolled to:
+        // at their end, to put the cursor after the value
+        if focusable.kind == FocusKind::TextInput {
+            let bounds = focusable.bounds;
+            events.append(&mut self.click(Point::new(
+                bounds.x + bounds.width - 2.,
+                bounds.y + moved + (bounds.height / 2.).floor(),
+            )));
+        }
+        Some(events)
+    }
+
+    /// Creates the events clicking at the given position
+    fn click(&mut self, position: Point) -> Vec<Event> {
+        self.cursor_position = position;
+        vec![
+            Event::Mouse(mouse::Event::CursorMoved {
+                x: position.x,
+                y: position.y,
+            }),
+            Event::Mouse(mouse::Event::Input {
+                state: ButtonState::Pressed,
+                button: mouse::Button::Left,
+            }),
+            Event::Mouse(mouse::Event::Input {
+                state: ButtonState::Released,
+                button: mouse::Button::Left,
+            }),
+        ]
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::{FocusKind, FocusRing};
+    use crate::primitive::Primitive;
+    use crate::PancursesRenderer;
+    use iced_native::input::{keyboard, keyboard::KeyCode, mouse, ButtonState};
+    use iced_native::widget::{button, scrollable};
+    use iced_native::{Event, Point, Rectangle};
+
+    fn key(state: ButtonState, key_code: KeyCode, shift: bool) -> Event {
+        Event::Keyboard(keyboard::Event::Input {
+            state,
+            key_code,
+            modifiers: keyboard::ModifiersState {
+                shift,
+                control: false,
+                alt: false,
+                logo: false,
+            },
+        })
+    }
+
+    fn ring() -> FocusRing {
+        let mut ring = FocusRing::default();
+        ring.register(
+            Rectangle {
+                x: 0.,
+                y: 0.,
+                width: 10.,
+                height: 3.,
+            },
+            FocusKind::Button,
+        );
+        ring.register(
+            Rectangle {
+                x: 0.,
+                y: 4.,
+                width: 10.,
+                height: 1.,
+            },
+            FocusKind::Checkbox,
+        );
+        ring.end_frame();
+        ring
+    }
+
+    #[test]
+    pub fn focus_ring_should_cycle_with_tab() {
+        let mut ring = ring();
+        assert_eq!(ring.focused_kind(), None);
+
+        // Tab presses are swallowed, along with their release
+        let tab = vec![
+            key(ButtonState::Pressed, KeyCode::Tab, false),
+            key(ButtonState::Released, KeyCode::Tab, false),
+        ];
+        assert_eq!(ring.handle(tab.clone()), vec![]);
+        assert_eq!(ring.focused_kind(), Some(FocusKind::Button));
+        ring.handle(tab.clone());
+        assert_eq!(ring.focused_kind(), Some(FocusKind::Checkbox));
+        ring.handle(tab.clone());
+        assert_eq!(ring.focused_kind(), Some(FocusKind::Button));
+
+        // Shift-Tab goes backwards
+        ring.handle(vec![key(ButtonState::Pressed, KeyCode::Tab, true)]);
+        assert_eq!(ring.focused_kind(), Some(FocusKind::Checkbox));
+
+        // Without widgets to focus, Tab is left to the application
+        let mut empty = FocusRing::default();
+        empty.end_frame();
+        assert_eq!(empty.handle(tab.clone()), tab);
+
+        // The focused widget is the one of the same kind drawn at the same place in the layout
+        // in the next frame
+        let checkbox = Rectangle {
+            x: 0.,
+            y: 4.,
+            width: 12.,
+            height: 1.,
+        };
+        assert!(!ring.register(Rectangle::default(), FocusKind::Checkbox));
+        assert!(!ring.register(checkbox, FocusKind::Radio));
+        assert!(ring.register(checkbox, FocusKind::Checkbox));
+        ring.end_frame();
+        assert_eq!(ring.focused, Some(2));
+
+        // The focus is removed when the widget is not drawn anymore
+        ring.register(Rectangle::default(), FocusKind::Checkbox);
+        ring.end_frame();
+        assert_eq!(ring.focused_kind(), None);
+    }
+
+    #[test]
+    pub fn focus_ring_should_activate_with_enter() {
+        let mut ring = ring();
+
+        // Without focus, Enter is left untouched
+        let enter = key(ButtonState::Pressed, KeyCode::Enter, false);
+        assert_eq!(ring.handle(vec![enter]), vec![enter]);
+
+        ring.handle(vec![key(ButtonState::Pressed, KeyCode::Tab, false)]);
+        assert_eq!(
+            ring.handle(vec![
+                enter,
+                key(ButtonState::Released, KeyCode::Enter, false)
+            ]),
+            vec![
+                Event::Mouse(mouse::Event::CursorMoved { x: 5., y: 1. }),
+                Event::Mouse(mouse::Event::Input {
+                    state: ButtonState::Pressed,
+                    button: mouse::Button::Left,
+                }),
+                Event::Mouse(mouse::Event::Input {
+                    state: ButtonState::Released,
+                    button: mouse::Button::Left,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn focus_ring_should_scroll_hovered_scrollable() {
+        let mut ring = FocusRing::default();
+        ring.register_scrollable(
+            Rectangle {
+                x: 0.,
+                y: 0.,
+                width: 10.,
+                height: 5.,
+            },
+            Rectangle {
+                x: 0.,
+                y: 0.,
+                width: 10.,
+                height: 20.,
+            },
+            0.,
+        );
+        ring.end_frame();
+
+        // Away from the scrollable, arrows are left untouched
+        let away = Event::Mouse(mouse::Event::CursorMoved { x: 20., y: 20. });
+        let down = key(ButtonState::Pressed, KeyCode::Down, false);
+        assert_eq!(ring.handle(vec![away, down]), vec![away, down]);
+
+        // Over it, they scroll it
+        let moved = Event::Mouse(mouse::Event::CursorMoved { x: 2., y: 3. });
+        ring.handle(vec![moved]);
+        assert_eq!(
+            ring.handle(vec![key(ButtonState::Pressed, KeyCode::PageDown, false)]),
+            vec![
+                Event::Mouse(mouse::Event::CursorMoved { x: 5., y: 2. }),
+                Event::Mouse(mouse::Event::WheelScrolled {
+                    delta: mouse::ScrollDelta::Pixels { x: 0., y: -4. },
+                }),
+                moved,
+            ]
+        );
+    }
+
+    #[test]
+    pub fn focus_ring_should_move_focused_slider() {
+        let mut ring = FocusRing::default();
+        let bounds = Rectangle {
+            x: 2.,
+            y: 1.,
+            width: 10.,
+            height: 1.,
+        };
+        // The value is at 3 tenths of the range, give or take a rounding error
+        ring.register_slider(bounds, 0.29999);
+        ring.end_frame();
+        ring.handle(vec![key(ButtonState::Pressed, KeyCode::Tab, false)]);
+
+        // Sliders are moved by clicking on them, on the column next to their handle
+        let click = |x| {
+            vec![
+                Event::Mouse(mouse::Event::CursorMoved { x, y: 1. }),
+                Event::Mouse(mouse::Event::Input {
+                    state: ButtonState::Pressed,
+                    button: mouse::Button::Left,
+                }),
+                Event::Mouse(mouse::Event::Input {
+                    state: ButtonState::Released,
+                    button: mouse::Button::Left,
+                }),
+            ]
+        };
+        let press = |key_code| vec![key(ButtonState::Pressed, key_code, false)];
+        assert_eq!(ring.handle(press(KeyCode::Right)), click(6.));
+        assert_eq!(ring.handle(press(KeyCode::Left)), click(4.));
+
+        // The end of the range is on the right of the last column
+        assert_eq!(ring.handle(press(KeyCode::End)), click(12.));
+        assert_eq!(ring.handle(press(KeyCode::Home)), click(2.));
+    }
+
+    #[test]
+    pub fn focus_ring_should_activate_button_in_scrolled_scrollable() {
+        let mut renderer = PancursesRenderer::headless(10, 5);
+        let mut draw_button = |y| {
+            let bounds = Rectangle {
+                x: 0.,
+                y,
+                width: 8.,
+                height: 1.,
+            };
+            button::Renderer::draw(
+                &mut renderer,
+                bounds,
+                Point::new(-1., -1.),
+                false,
+                None,
+                0,
+                Primitive::Empty,
+            )
+        };
+        let content = Primitive::Group(vec![draw_button(10.), draw_button(14.)]);
+        let bounds = Rectangle {
+            x: 0.,
+            y: 0.,
+            width: 10.,
+            height: 5.,
+        };
+        let content_bounds = Rectangle {
+            height: 20.,
+            ..bounds
+        };
+        // The content is scrolled by 8 lines: the first button is on the line 2 of the screen,
+        // and the second one is hidden below the scrollable
+        let primitive = scrollable::Renderer::draw(
+            &mut renderer,
+            &scrollable::State::new(),
+            bounds,
+            content_bounds,
+            false,
+            false,
+            None,
+            8,
+            content,
+        );
+        renderer.draw(primitive);
+
+        let press = |key_code| key(ButtonState::Pressed, key_code, false);
+        let click = |x, y| {
+            vec![
+                Event::Mouse(mouse::Event::CursorMoved { x, y }),
+                Event::Mouse(mouse::Event::Input {
+                    state: ButtonState::Pressed,
+                    button: mouse::Button::Left,
+                }),
+                Event::Mouse(mouse::Event::Input {
+                    state: ButtonState::Released,
+                    button: mouse::Button::Left,
+                }),
+            ]
+        };
+        let backend = renderer.headless_backend_mut().unwrap();
+        backend.push_events(vec![press(KeyCode::Tab), press(KeyCode::Enter)]);
+        backend.push_events(vec![press(KeyCode::Tab)]);
+        assert_eq!(renderer.handle(), Some(click(4., 2.)));
+
+        // Moving the focus to the hidden button scrolls it into view
+        assert_eq!(
+            renderer.handle(),
+            Some(vec![
+                Event::Mouse(mouse::Event::CursorMoved { x: 5., y: 2. }),
+                Event::Mouse(mouse::Event::WheelScrolled {
+                    delta: mouse::ScrollDelta::Pixels { x: 0., y: -2. },
+                }),
+                Event::Mouse(mouse::Event::CursorMoved { x: 4., y: 2. }),
+            ])
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use crate::style::Style;
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A character printed on the screen, along with its style.
+///
+/// Characters taking two columns, such as CJK ideographs, are followed by a cell with an empty
+/// content, standing for the second column they cover.
+pub struct Cell {
+    /// Grapheme cluster displayed in the cell: a character and its combining marks
+    pub content: String,
+    /// Colors of the cell
+    pub style: Style,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell {
+            content: " ".into(),
+            style: Default::default(),
+        }
+    }
+}
+
+impl Cell {
+    /// Returns whether the cell holds a character taking two columns
+    pub fn is_wide(&self) -> bool {
+        self.content.width() > 1
+    }
+
+    /// Returns whether the cell is covered by the wide character on its left
+    pub fn is_continuation(&self) -> bool {
+        self.content.is_empty()
+    }
+}
+
+/// A grid of cells, holding the content of the whole screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    /// Number of columns of the frame
+    width: u16,
+    /// Number of lines of the frame
+    height: u16,
+    /// Cells of the frame, line by line
+    cells: Vec<Cell>,
+}
+
+impl Frame {
+    /// Creates a blank frame of the given size
+    pub fn new(width: u16, height: u16) -> Self {
+        Frame {
+            width,
+            height,
+            cells: vec![Cell::default(); width as usize * height as usize],
+        }
+    }
+
+    /// Gets the size of the frame, as (columns, lines)
+    pub fn size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    /// Gets the cell at the given position, if it is in the frame
+    pub fn get(&self, x: u16, y: u16) -> Option<&Cell> {
+        if x < self.width && y < self.height {
+            self.cells
+                .get(y as usize * self.width as usize + x as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Replaces the cell at the given position, if it is in the frame.
+    /// Wide characters partially overwritten are replaced by spaces.
+    pub fn set(&mut self, x: u16, y: u16, cell: Cell) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let idx = y as usize * self.width as usize + x as usize;
+        // A continuation cell being set belongs to the wide character just set on its left
+        if self.cells[idx].is_continuation() && !cell.is_continuation() && x > 0 {
+            self.cells[idx - 1].content = " ".into();
+        }
+        if self.cells[idx].is_wide() && x + 1 < self.width {
+            self.cells[idx + 1].content = " ".into();
+        }
+        self.cells[idx] = cell;
+    }
+
+    /// Blanks every cell of the frame
+    pub fn clear(&mut self) {
+        self.cells
+            .iter_mut()
+            .for_each(|cell| *cell = Cell::default());
+    }
+
+    /// Gets the cells that differ from the ones of a previous frame of the same size,
+    /// along with their position
+    pub fn diff<'a>(&'a self, previous: &'a Frame) -> impl Iterator<Item = (u16, u16, &'a Cell)> {
+        let width = self.width as usize;
+        self.cells
+            .iter()
+            .zip(previous.cells.iter())
+            .enumerate()
+            .filter(|(_, (cell, previous))| cell != previous)
+            .map(move |(idx, (cell, _))| ((idx % width) as u16, (idx / width) as u16, cell))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::{Cell, Frame};
+    use crate::primitive::Primitive;
+    use crate::style::Style;
+    use crate::PancursesRenderer;
+    use iced_native::Rectangle;
+
+    #[test]
+    pub fn frame_diff_should_work() {
+        let previous = Frame::new(3, 2);
+        let mut frame = Frame::new(3, 2);
+        let cell = Cell {
+            content: "a".into(),
+            ..Default::default()
+        };
+        frame.set(2, 1, cell.clone());
+        assert_eq!(
+            frame.diff(&previous).collect::<Vec<_>>(),
+            vec![(2, 1, &cell)]
+        );
+        assert_eq!(frame.diff(&frame).count(), 0);
+    }
+
+    #[test]
+    pub fn frame_should_erase_overwritten_wide_chars() {
+        let mut renderer = PancursesRenderer::headless(6, 1);
+        let text = |content: &str, x: f32| {
+            Primitive::Text(
+                vec![content.into()],
+                Rectangle {
+                    x,
+                    y: 0.,
+                    width: 4.,
+                    height: 1.,
+                },
+                Style::default(),
+            )
+        };
+        // Each ideograph takes two columns: overwriting the second column of one erases it
+        renderer.draw(Primitive::Group(vec![text("日本", 0.), text("a", 3.)]));
+        assert_eq!(renderer.headless_backend().unwrap().lines(), vec!["日 a  "]);
+    }
+
+    #[test]
+    pub fn redraw_should_erase_previous_frame() {
+        let text = |content: &str| {
+            Primitive::Text(
+                vec![content.into()],
+                Rectangle {
+                    x: 0.,
+                    y: 0.,
+                    width: 5.,
+                    height: 1.,
+                },
+                Style::default(),
+            )
+        };
+        let mut renderer = PancursesRenderer::headless(5, 1);
+        renderer.draw(text("Hello"));
+        renderer.draw(text("Hi"));
+        assert_eq!(renderer.headless_backend().unwrap().lines(), vec!["Hi   "]);
+    }
+}
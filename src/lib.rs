@@ -2,13 +2,27 @@
 //! Allows to create TUI application using iced as the GUI framework
 
 mod application;
+mod backend;
 mod colors;
+mod figlet;
+mod focus;
+mod frame;
 mod primitive;
 mod renderer;
 mod sandbox;
+mod snapshot;
+mod style;
 mod subscription;
+mod widget;
 
 // Conveniently reexports common structs that the user might want to use in their application.
 pub use application::Application;
-pub use renderer::PancursesRenderer;
+pub use backend::{AsAny, Backend, HeadlessBackend, PancursesBackend};
+pub use colors::ColorRegistryStats;
+pub use figlet::{FigletFont, FontError};
+pub use frame::Cell;
+pub use renderer::{AsciiArt, ImageProtocol, PancursesRenderer, WrapMode};
 pub use sandbox::Sandbox;
+pub use snapshot::{render, render_to_ansi, render_to_string};
+pub use style::Style;
+pub use widget::{BigText, Canvas};
@@ -1,24 +1,39 @@
+use crate::style::Style;
 use iced_native::{Color, Rectangle};
+use std::rc::Rc;
 
 pub enum Primitive {
-    Char(i32, i32, char),
-    BoxDisplay(Rectangle),
+    Char(i32, i32, char, Style),
+    Cursor(i32, i32),
+    BoxDisplay(Rectangle, Style),
     Empty,
     Group(Vec<Primitive>),
-    Text(Vec<String>, Rectangle, Color),
+    Text(Vec<String>, Rectangle, Style),
+    /// A rectangle filled with a background color, with an optional border
+    Quad(Rectangle, Color, Option<Style>),
+    /// A primitive only drawn inside of the given rectangle
+    Clip(Rectangle, Box<Primitive>),
+    /// An image drawn by the terminal from an escape sequence, covering the cells of the given
+    /// rectangle
+    Graphics(Rectangle, Rc<str>),
 }
 
 impl Primitive {
     pub fn with_offset(self, offset: i32) -> Primitive {
         match self {
-            Primitive::BoxDisplay(mut bounds) => {
+            Primitive::BoxDisplay(mut bounds, style) => {
                 bounds.y -= offset as f32;
-                Primitive::BoxDisplay(bounds)
+                Primitive::BoxDisplay(bounds, style)
             }
-            Primitive::Char(x, y, content) => Primitive::Char(x, y - offset, content),
-            Primitive::Text(content, mut bounds, color) => {
+            Primitive::Char(x, y, content, style) => Primitive::Char(x, y - offset, content, style),
+            Primitive::Cursor(x, y) => Primitive::Cursor(x, y - offset),
+            Primitive::Quad(mut bounds, background, border) => {
                 bounds.y -= offset as f32;
-                Primitive::Text(content, bounds, color)
+                Primitive::Quad(bounds, background, border)
+            }
+            Primitive::Text(content, mut bounds, style) => {
+                bounds.y -= offset as f32;
+                Primitive::Text(content, bounds, style)
             }
             Primitive::Group(primitives) => Primitive::Group(
                 primitives
@@ -26,6 +41,33 @@ impl Primitive {
                     .map(|p| p.with_offset(offset))
                     .collect(),
             ),
+            Primitive::Clip(mut bounds, content) => {
+                bounds.y -= offset as f32;
+                Primitive::Clip(bounds, Box::new(content.with_offset(offset)))
+            }
+            Primitive::Graphics(mut bounds, sequence) => {
+                bounds.y -= offset as f32;
+                Primitive::Graphics(bounds, sequence)
+            }
+            _ => self,
+        }
+    }
+
+    /// Changes the color of every text of the primitive
+    pub fn with_color(self, color: Color) -> Primitive {
+        match self {
+            Primitive::Text(content, bounds, style) => {
+                Primitive::Text(content, bounds, style.foreground(color))
+            }
+            Primitive::Group(primitives) => Primitive::Group(
+                primitives
+                    .into_iter()
+                    .map(|p| p.with_color(color))
+                    .collect(),
+            ),
+            Primitive::Clip(bounds, content) => {
+                Primitive::Clip(bounds, Box::new(content.with_color(color)))
+            }
             _ => self,
         }
     }
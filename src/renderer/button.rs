@@ -1,4 +1,6 @@
+use crate::focus::{FocusKind, FOCUS_COLOR};
 use crate::primitive::Primitive;
+use crate::style::Style;
 use crate::PancursesRenderer;
 use iced_native::widget::button;
 use iced_native::{Background, Point, Rectangle};
@@ -9,10 +11,73 @@ impl button::Renderer for PancursesRenderer {
         bounds: Rectangle,
         _cursor_position: Point,
         _is_pressed: bool,
-        _background: Option<Background>,
+        background: Option<Background>,
         _border_radius: u16,
         content: Self::Output,
     ) -> Self::Output {
-        Primitive::Group(vec![Primitive::BoxDisplay(bounds), content])
+        let content = if self.focus.register(bounds, FocusKind::Button) {
+            content.with_color(FOCUS_COLOR)
+        } else {
+            content
+        };
+        let frame = match background {
+            Some(Background::Color(color)) => {
+                Primitive::Quad(bounds, color, Some(Style::default()))
+            }
+            None => Primitive::BoxDisplay(bounds, Style::default()),
+        };
+        Primitive::Group(vec![frame, content])
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use crate::primitive::Primitive;
+    use crate::style::Style;
+    use crate::PancursesRenderer;
+    use iced_native::widget::button;
+    use iced_native::{Background, Color, Point, Rectangle};
+
+    #[test]
+    pub fn button_should_fill_background() {
+        let mut renderer = PancursesRenderer::headless(6, 3);
+        let bounds = Rectangle {
+            x: 0.,
+            y: 0.,
+            width: 6.,
+            height: 3.,
+        };
+        let label = Primitive::Text(
+            vec!["Ok".into()],
+            Rectangle {
+                x: 2.,
+                y: 1.,
+                width: 2.,
+                height: 1.,
+            },
+            Style::default(),
+        );
+        let blue = Color::from_rgb(0., 0., 1.);
+        let primitive = button::Renderer::draw(
+            &mut renderer,
+            bounds,
+            Point::new(-1., -1.),
+            false,
+            Some(Background::Color(blue)),
+            0,
+            label,
+        );
+        renderer.draw(primitive);
+        // The whole button is blue, the label included:
+        //
+        // ┌────┐
+        // │ Ok │
+        // └────┘
+        let backend = renderer.headless_backend().unwrap();
+        assert_eq!(backend.lines(), vec!["┌────┐", "│ Ok │", "└────┘"]);
+        for (x, y) in &[(0, 0), (1, 1), (2, 1), (3, 2)] {
+            assert_eq!(backend.cell(*x, *y).unwrap().style.background, Some(blue));
+        }
     }
 }
@@ -1,4 +1,6 @@
+use crate::focus::{FocusKind, FOCUS_COLOR};
 use crate::primitive::Primitive;
+use crate::style::Style;
 use crate::PancursesRenderer;
 
 use iced_native::widget::checkbox::Renderer as CheckboxRenderer;
@@ -17,9 +19,22 @@ impl CheckboxRenderer for PancursesRenderer {
         label: Primitive,
     ) -> Primitive {
         let boxchar = if is_checked { 'x' } else { 'o' };
-        Primitive::Group(vec![
-            Primitive::Char(bounds.x as i32, bounds.y as i32, boxchar),
-            label,
-        ])
+        // The focused checkbox is highlighted by coloring its indicator and its label
+        if self.focus.register(bounds, FocusKind::Checkbox) {
+            Primitive::Group(vec![
+                Primitive::Char(
+                    bounds.x as i32,
+                    bounds.y as i32,
+                    boxchar,
+                    Style::new(FOCUS_COLOR),
+                ),
+                label.with_color(FOCUS_COLOR),
+            ])
+        } else {
+            Primitive::Group(vec![
+                Primitive::Char(bounds.x as i32, bounds.y as i32, boxchar, Style::default()),
+                label,
+            ])
+        }
     }
 }
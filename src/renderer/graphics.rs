@@ -0,0 +1,266 @@
+//! Terminal graphics protocols, drawing images with real pixels.
+//!
+//! Images are encoded into escape sequences, which are written by the backend once the cells
+//! they cover are displayed.
+
+use super::image::ALPHA_THRESHOLD;
+use ::image::{Rgba, RgbaImage};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// Size of the chunks of data in which images are sent with the kitty protocol
+const KITTY_CHUNK_SIZE: usize = 4096;
+/// Escape sequence deleting every image shown with the kitty protocol
+const KITTY_DELETE_ALL: &str = "\x1b_Ga=d,q=2\x1b\\";
+/// Name of the environment variable forcing the protocol used to draw images:
+/// `kitty`, `sixel`, `blocks` or `ascii`
+const PROTOCOL_VAR: &str = "ICED_PANCURSES_IMAGES";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// The way images are drawn in the terminal
+pub enum ImageProtocol {
+    /// Images are drawn with colored half blocks, which every terminal supports
+    #[default]
+    HalfBlocks,
+    /// Images are drawn with ASCII characters, chosen from the luminance of the pixels:
+    /// see `AsciiArt`
+    Ascii,
+    /// Images are drawn with sixels, supported by xterm, mlterm, foot, and a few others
+    Sixel,
+    /// Images are drawn with the graphics protocol of kitty, also supported by WezTerm
+    /// and ghostty
+    Kitty,
+}
+
+impl ImageProtocol {
+    /// Detects the best protocol supported by the terminal, from the environment variables.
+    /// The protocol can be forced with the `ICED_PANCURSES_IMAGES` variable.
+    pub fn detect() -> Self {
+        ImageProtocol::from_environment(|name| std::env::var(name).ok())
+    }
+
+    /// Detects the protocol supported by the terminal, from the given environment variables
+    pub fn from_environment<F: Fn(&str) -> Option<String>>(var: F) -> Self {
+        match var(PROTOCOL_VAR).as_deref() {
+            Some("kitty") => return ImageProtocol::Kitty,
+            Some("sixel") => return ImageProtocol::Sixel,
+            Some("blocks") => return ImageProtocol::HalfBlocks,
+            Some("ascii") => return ImageProtocol::Ascii,
+            _ => (),
+        }
+        let term = var("TERM").unwrap_or_default();
+        let term_program = var("TERM_PROGRAM").unwrap_or_default();
+        if var("KITTY_WINDOW_ID").is_some()
+            || term.contains("kitty")
+            || term.contains("ghostty")
+            || term_program == "WezTerm"
+        {
+            ImageProtocol::Kitty
+        } else if term.contains("sixel")
+            || ["mlterm", "foot", "contour"]
+                .iter()
+                .any(|name| term.starts_with(name))
+        {
+            ImageProtocol::Sixel
+        } else if term == "dumb" || term.starts_with("vt") {
+            // Terminals without colors, nor block characters
+            ImageProtocol::Ascii
+        } else {
+            ImageProtocol::HalfBlocks
+        }
+    }
+
+    /// Returns whether images are drawn with pixels, instead of characters
+    pub fn uses_pixels(self) -> bool {
+        matches!(self, ImageProtocol::Sixel | ImageProtocol::Kitty)
+    }
+
+    /// Encodes an image into the escape sequence drawing it at the position of the cursor,
+    /// if the protocol uses pixels
+    pub fn encode(self, image: &RgbaImage) -> Option<String> {
+        match self {
+            ImageProtocol::HalfBlocks | ImageProtocol::Ascii => None,
+            ImageProtocol::Sixel => Some(sixel(image)),
+            ImageProtocol::Kitty => Some(kitty(image)),
+        }
+    }
+
+    /// Gets the escape sequence erasing the images drawn before, if they are not erased
+    /// along with the screen
+    pub fn clear_sequence(self) -> Option<&'static str> {
+        match self {
+            ImageProtocol::Kitty => Some(KITTY_DELETE_ALL),
+            _ => None,
+        }
+    }
+}
+
+/// Gets the color register of a pixel in the 6x6x6 color cube used by sixel images,
+/// if it is opaque enough to be drawn
+fn sixel_register(pixel: &Rgba<u8>) -> Option<u16> {
+    let [r, g, b, a] = pixel.0;
+    if a < ALPHA_THRESHOLD {
+        return None;
+    }
+    let level = |value: u8| (value as u16 * 5 + 127) / 255;
+    Some(level(r) * 36 + level(g) * 6 + level(b))
+}
+
+/// Appends a run of identical sixels, compressing it if it is long enough
+fn push_sixels(output: &mut String, sixel: char, count: usize) {
+    if count >= 4 {
+        let _ = write!(output, "!{}{}", count, sixel);
+    } else {
+        output.extend(std::iter::repeat_n(sixel, count));
+    }
+}
+
+/// Encodes an image with sixels.
+///
+/// Colors are reduced to a 6x6x6 color cube, and transparent pixels are left untouched.
+pub fn sixel(image: &RgbaImage) -> String {
+    let (width, height) = image.dimensions();
+    let mut output = format!("\x1bP0;1;0q\"1;1;{};{}", width, height);
+    let registers: BTreeSet<u16> = image.pixels().filter_map(sixel_register).collect();
+    for register in &registers {
+        let level = |component: u16| component * 20;
+        let _ = write!(
+            output,
+            "#{};2;{};{};{}",
+            register,
+            level(register / 36),
+            level(register / 6 % 6),
+            level(register % 6)
+        );
+    }
+    // Each band of six rows of pixels is drawn once for every color it contains
+    for band in (0..height).step_by(6) {
+        let rows = band..(band + 6).min(height);
+        let band_registers: BTreeSet<u16> = rows
+            .clone()
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter_map(|(x, y)| sixel_register(image.get_pixel(x, y)))
+            .collect();
+        for (idx, register) in band_registers.iter().enumerate() {
+            if idx > 0 {
+                output.push('$');
+            }
+            let _ = write!(output, "#{}", register);
+            let mut run = None;
+            for x in 0..width {
+                let bits = rows
+                    .clone()
+                    .filter(|y| sixel_register(image.get_pixel(x, *y)) == Some(*register))
+                    .fold(0, |bits, y| bits | 1 << (y - band));
+                let sixel = (63 + bits) as u8 as char;
+                run = match run {
+                    Some((current, count)) if current == sixel => Some((current, count + 1)),
+                    Some((current, count)) => {
+                        push_sixels(&mut output, current, count);
+                        Some((sixel, 1))
+                    }
+                    None => Some((sixel, 1)),
+                };
+            }
+            // Blank sixels at the end of the line do not need to be drawn
+            if let Some((sixel, count)) = run.filter(|(sixel, _)| *sixel != '?') {
+                push_sixels(&mut output, sixel, count);
+            }
+        }
+        output.push('-');
+    }
+    output.push_str("\x1b\\");
+    output
+}
+
+/// Encodes an image with the kitty graphics protocol.
+///
+/// Pixels are sent as they are, in chunks. The terminal is asked not to respond,
+/// and not to move the cursor.
+pub fn kitty(image: &RgbaImage) -> String {
+    let (width, height) = image.dimensions();
+    let data = base64::encode(image.as_raw());
+    // Base64 only contains ASCII characters, which are split anywhere
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+    let mut output = String::new();
+    for (idx, chunk) in chunks.iter().enumerate() {
+        let mut keys = Vec::new();
+        if idx == 0 {
+            keys.push(format!("a=T,f=32,s={},v={},C=1,q=2", width, height));
+        }
+        if chunks.len() > 1 {
+            keys.push(format!("m={}", (idx + 1 < chunks.len()) as u8));
+        }
+        output.push_str("\x1b_G");
+        output.push_str(&keys.join(","));
+        output.push(';');
+        output.push_str(std::str::from_utf8(chunk).unwrap_or_default());
+        output.push_str("\x1b\\");
+    }
+    output
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::{kitty, sixel, ImageProtocol};
+    use ::image::{Rgba, RgbaImage};
+
+    #[test]
+    pub fn image_protocol_should_be_detected() {
+        let detect = |vars: &'static [(&'static str, &'static str)]| {
+            ImageProtocol::from_environment(|name| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            })
+        };
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color")]),
+            ImageProtocol::HalfBlocks
+        );
+        assert_eq!(detect(&[("TERM", "xterm-kitty")]), ImageProtocol::Kitty);
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color"), ("TERM_PROGRAM", "WezTerm")]),
+            ImageProtocol::Kitty
+        );
+        assert_eq!(detect(&[("TERM", "foot")]), ImageProtocol::Sixel);
+        assert_eq!(detect(&[("TERM", "vt100")]), ImageProtocol::Ascii);
+        // The protocol can be forced
+        assert_eq!(
+            detect(&[("TERM", "xterm-kitty"), ("ICED_PANCURSES_IMAGES", "blocks")]),
+            ImageProtocol::HalfBlocks
+        );
+    }
+
+    #[test]
+    pub fn sixel_should_encode_image() {
+        // A line of five red pixels, with a blue one under the first one
+        let mut image = RgbaImage::new(5, 2);
+        (0..5).for_each(|x| image.put_pixel(x, 0, Rgba([255, 0, 0, 255])));
+        image.put_pixel(0, 1, Rgba([0, 0, 255, 255]));
+        // Colors are defined first, in percents.
+        // Each color is then drawn, the bits of the sixels being the rows of the pixels
+        // of the color: 63 + 1 is '@' for the top one, and 63 + 2 is 'A' for the second one.
+        assert_eq!(
+            sixel(&image),
+            "\x1bP0;1;0q\"1;1;5;2#5;2;0;0;100#180;2;100;0;0#5A$#180!5@-\x1b\\"
+        );
+    }
+
+    #[test]
+    pub fn kitty_should_encode_image() {
+        let image = RgbaImage::from_pixel(1, 1, Rgba([255, 0, 0, 255]));
+        assert_eq!(
+            kitty(&image),
+            "\x1b_Ga=T,f=32,s=1,v=1,C=1,q=2;/wAA/w==\x1b\\"
+        );
+
+        // Large images are sent in several chunks
+        let image = RgbaImage::new(64, 32);
+        let sequence = kitty(&image);
+        assert!(sequence.starts_with("\x1b_Ga=T,f=32,s=64,v=32,C=1,q=2,m=1;"));
+        assert_eq!(sequence.matches("\x1b_Gm=1;").count(), 1);
+        assert_eq!(sequence.matches("\x1b_Gm=0;").count(), 1);
+    }
+}
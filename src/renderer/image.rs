@@ -1,29 +1,570 @@
+use super::graphics::ImageProtocol;
 use crate::primitive::Primitive;
+use crate::style::Style;
 use crate::PancursesRenderer;
+use ::image::{imageops, RgbaImage};
 use iced_native::widget::image;
-use iced_native::{Font, HorizontalAlignment, Layout, VerticalAlignment};
+use iced_native::{Color, Font, HorizontalAlignment, Layout, Rectangle, VerticalAlignment};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::rc::Rc;
 
-// TODO: Properly support image rendering using w3img backend or fallback pixel buffer
+/// Character drawing the upper pixel of a cell with its foreground, and the lower one with its
+/// background
+const UPPER_HALF: char = '▀';
+/// Character drawing the lower pixel of a cell with its foreground
+const LOWER_HALF: char = '▄';
+/// Pixels less opaque than this are not drawn, keeping the background of their cell
+pub const ALPHA_THRESHOLD: u8 = 128;
+/// Characters of increasing density used to draw images with ASCII characters by default
+const DEFAULT_RAMP: &str = " .:-=+*#%@";
+/// Size of the box drawn in place of images that cannot be decoded
+pub const INVALID_DIMENSIONS: (u32, u32) = (15, 5);
+
+/// Decoded images, by id of their handle.
+/// Images that could not be decoded are kept as well, to avoid decoding them again.
+///
+/// The last escape sequence drawing each image is kept too, with its protocol and size.
+#[derive(Debug, Default)]
+pub struct ImageCache {
+    images: HashMap<u64, Option<Rc<RgbaImage>>>,
+    sequences: HashMap<ImageKey, Sequence>,
+}
+
+/// Identifies a drawn image by the id of its handle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageKey {
+    Image(u64),
+    Svg(u64),
+}
+
+/// An escape sequence drawing an image
+#[derive(Debug)]
+struct Sequence {
+    protocol: ImageProtocol,
+    /// Size of the image in pixels
+    size: (u32, u32),
+    content: Rc<str>,
+}
+
+impl ImageCache {
+    /// Gets the decoded image of a handle, decoding it if it is not in the cache
+    pub fn get(&mut self, handle: &image::Handle) -> Option<Rc<RgbaImage>> {
+        self.images
+            .entry(handle.id())
+            .or_insert_with(|| decode(handle).map(Rc::new))
+            .clone()
+    }
+
+    /// Gets the escape sequence drawing an image at the given size in pixels,
+    /// scaling and encoding it if it has not been drawn at this size
+    fn sequence(
+        &mut self,
+        key: ImageKey,
+        image: &RgbaImage,
+        protocol: ImageProtocol,
+        size: (u32, u32),
+    ) -> Rc<str> {
+        match self.sequences.get(&key) {
+            Some(sequence) if (sequence.protocol, sequence.size) == (protocol, size) => {
+                sequence.content.clone()
+            }
+            _ => {
+                let content: Rc<str> = protocol
+                    .encode(&resize(image, size))
+                    .unwrap_or_default()
+                    .into();
+                let sequence = Sequence {
+                    protocol,
+                    size,
+                    content: content.clone(),
+                };
+                self.sequences.insert(key, sequence);
+                content
+            }
+        }
+    }
+}
+
+/// Decodes the image of a handle, from a file or from memory
+fn decode(handle: &image::Handle) -> Option<RgbaImage> {
+    let image = match handle.data() {
+        image::Data::Path(path) => ::image::open(path),
+        image::Data::Bytes(bytes) => ::image::load_from_memory(bytes),
+    };
+    image.ok().map(|image| image.to_rgba8())
+}
+
+/// How images are drawn with ASCII characters, when using `ImageProtocol::Ascii`.
+///
+/// Each pixel is drawn with a character of the ramp, chosen from its luminance: the ramp goes
+/// from the character of the darkest pixels to the one of the brightest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsciiArt {
+    ramp: Vec<char>,
+    dithering: bool,
+    color: bool,
+}
+
+impl Default for AsciiArt {
+    /// Draws images in color with the ` .:-=+*#%@` ramp, without dithering
+    fn default() -> Self {
+        AsciiArt {
+            ramp: DEFAULT_RAMP.chars().collect(),
+            dithering: false,
+            color: true,
+        }
+    }
+}
+
+impl AsciiArt {
+    /// Creates the default configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the characters used to draw pixels, from the darkest to the brightest.
+    /// They should be one column wide.
+    pub fn ramp(mut self, ramp: &str) -> Self {
+        self.ramp = ramp.chars().collect();
+        self
+    }
+
+    /// Sets whether the error made by approaching the luminance of a pixel is spread on the
+    /// next pixels, using Floyd-Steinberg dithering. This makes gradients look smoother.
+    pub fn dithering(mut self, dithering: bool) -> Self {
+        self.dithering = dithering;
+        self
+    }
+
+    /// Sets whether characters are drawn with the color of their pixel, which is the case by
+    /// default. Otherwise, only their density shows the image.
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Converts an image to characters, one for each pixel, with their color.
+    /// Transparent pixels are left empty.
+    pub fn convert(&self, image: &RgbaImage) -> Vec<Vec<Option<(char, Color)>>> {
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let mut luminances: Vec<Vec<f32>> = image
+            .rows()
+            .map(|row| {
+                row.map(|pixel| {
+                    let [r, g, b, _] = pixel.0;
+                    (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32) / 255.
+                })
+                .collect()
+            })
+            .collect();
+        let last = match self.ramp.len() {
+            0 => return vec![vec![None; width]; height],
+            length => (length - 1) as f32,
+        };
+        let mut lines = Vec::with_capacity(height);
+        for y in 0..height {
+            let mut line = Vec::with_capacity(width);
+            for x in 0..width {
+                let color = pixel_color(image.get_pixel(x as u32, y as u32));
+                let luminance = luminances[y][x].clamp(0., 1.);
+                let level = (luminance * last).round();
+                if self.dithering {
+                    let error = luminances[y][x] - level / last;
+                    let mut spread = |x: usize, y: usize, ratio: f32| {
+                        if let Some(luminance) = luminances.get_mut(y).and_then(|l| l.get_mut(x)) {
+                            *luminance += error * ratio;
+                        }
+                    };
+                    spread(x + 1, y, 7. / 16.);
+                    if x > 0 {
+                        spread(x - 1, y + 1, 3. / 16.);
+                    }
+                    spread(x, y + 1, 5. / 16.);
+                    spread(x + 1, y + 1, 1. / 16.);
+                }
+                line.push(color.map(|color| (self.ramp[level as usize], color)));
+            }
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+/// Gets the size in cells of an image drawn with characters: each cell shows two pixels on top
+/// of each other with half blocks, or a single pixel, twice as tall as wide, with ASCII art
+fn cell_dimensions((width, height): (u32, u32)) -> (u32, u32) {
+    (width, height.div_ceil(2))
+}
+
+/// Gets the size in cells of an image drawn with real pixels
+fn pixel_cell_dimensions((width, height): (u32, u32), cell_size: (u16, u16)) -> (u32, u32) {
+    (
+        width.div_ceil(cell_size.0.max(1) as u32),
+        height.div_ceil(cell_size.1.max(1) as u32),
+    )
+}
+
+/// Gets the size of an image of the given size scaled to fit in the maximum size,
+/// preserving its aspect ratio. Nothing can be drawn if either size is empty.
+fn fit_size((width, height): (u32, u32), max_width: u32, max_height: u32) -> Option<(u32, u32)> {
+    if width == 0 || height == 0 || max_width == 0 || max_height == 0 {
+        return None;
+    }
+    let scale = (max_width as f32 / width as f32).min(max_height as f32 / height as f32);
+    Some((
+        ((width as f32 * scale).round() as u32).clamp(1, max_width),
+        ((height as f32 * scale).round() as u32).clamp(1, max_height),
+    ))
+}
+
+/// Scales an image to the given size, if it is not at this size already
+fn resize(image: &RgbaImage, (width, height): (u32, u32)) -> Cow<'_, RgbaImage> {
+    if (width, height) == image.dimensions() {
+        Cow::Borrowed(image)
+    } else {
+        Cow::Owned(imageops::resize(
+            image,
+            width,
+            height,
+            imageops::FilterType::Triangle,
+        ))
+    }
+}
+
+/// Gets the position of the top left corner of an area of the given size centered in the bounds
+fn centered(bounds: Rectangle, (width, height): (u32, u32)) -> (i32, i32) {
+    (
+        bounds.x as i32 + (bounds.width as i32 - width as i32) / 2,
+        bounds.y as i32 + (bounds.height as i32 - height as i32) / 2,
+    )
+}
+
+/// Gets the color of a pixel, if it is opaque enough to be drawn
+fn pixel_color(pixel: &::image::Rgba<u8>) -> Option<Color> {
+    let [r, g, b, a] = pixel.0;
+    if a < ALPHA_THRESHOLD {
+        return None;
+    }
+    Some(Color::from_rgb(
+        r as f32 / 255.,
+        g as f32 / 255.,
+        b as f32 / 255.,
+    ))
+}
+
+/// Draws an image with half blocks, scaled to fit in the given bounds and centered in them.
+/// The aspect ratio of the image is preserved, cells being twice as tall as wide.
+///
+/// Colors are approached by the closest ones the terminal can display, by the backend.
+fn draw_half_blocks(image: &RgbaImage, bounds: Rectangle) -> Primitive {
+    let size = match fit_size(
+        image.dimensions(),
+        bounds.width as u32,
+        bounds.height as u32 * 2,
+    ) {
+        Some(size) => size,
+        None => return Primitive::Empty,
+    };
+    let scaled = resize(image, size);
+    let (cells_width, cells_height) = cell_dimensions(scaled.dimensions());
+    let (x, y) = centered(bounds, (cells_width, cells_height));
+    let mut cells = Vec::new();
+    for row in 0..cells_height {
+        for column in 0..cells_width {
+            let top = pixel_color(scaled.get_pixel(column, row * 2));
+            let bottom = if row * 2 + 1 < scaled.height() {
+                pixel_color(scaled.get_pixel(column, row * 2 + 1))
+            } else {
+                None
+            };
+            // Transparent halves keep the background of the cell
+            let (content, style) = match (top, bottom) {
+                (Some(top), Some(bottom)) => (UPPER_HALF, Style::new(top).background(bottom)),
+                (Some(top), None) => (UPPER_HALF, Style::new(top)),
+                (None, Some(bottom)) => (LOWER_HALF, Style::new(bottom)),
+                (None, None) => continue,
+            };
+            cells.push(Primitive::Char(
+                x + column as i32,
+                y + row as i32,
+                content,
+                style,
+            ));
+        }
+    }
+    Primitive::Group(cells)
+}
+
+/// Draws an image with ASCII characters, scaled to fit in the given bounds and centered in them.
+/// The aspect ratio of the image is preserved, cells being twice as tall as wide.
+fn draw_ascii(image: &RgbaImage, bounds: Rectangle, ascii_art: &AsciiArt) -> Primitive {
+    let (width, height) = match fit_size(
+        image.dimensions(),
+        bounds.width as u32,
+        bounds.height as u32 * 2,
+    ) {
+        Some(size) => size,
+        None => return Primitive::Empty,
+    };
+    // Each character stands for two pixels on top of each other
+    let size = (width, height.div_ceil(2));
+    let scaled = resize(image, size);
+    let (x, y) = centered(bounds, size);
+    let mut cells = Vec::new();
+    for (row, line) in ascii_art.convert(&scaled).into_iter().enumerate() {
+        for (column, cell) in line.into_iter().enumerate() {
+            if let Some((content, color)) = cell {
+                let style = if ascii_art.color {
+                    Style::new(color)
+                } else {
+                    Style::default()
+                };
+                cells.push(Primitive::Char(
+                    x + column as i32,
+                    y + row as i32,
+                    content,
+                    style,
+                ));
+            }
+        }
+    }
+    Primitive::Group(cells)
+}
 
 impl image::Renderer for PancursesRenderer {
-    fn dimensions(&self, _path: &image::Handle) -> (u32, u32) {
-        (15, 5)
+    fn dimensions(&self, handle: &image::Handle) -> (u32, u32) {
+        match self.images.borrow_mut().get(handle) {
+            Some(image) => self.raster_dimensions(image.dimensions()),
+            None => INVALID_DIMENSIONS,
+        }
     }
 
-    fn draw(&mut self, _path: image::Handle, layout: Layout) -> Primitive {
-        let mut bounds = layout.bounds();
-        bounds.x += 1.;
-        bounds.y += 1.;
+    fn draw(&mut self, handle: image::Handle, layout: Layout) -> Primitive {
+        let image = self.images.borrow_mut().get(&handle);
+        match image {
+            Some(image) => self.draw_raster(ImageKey::Image(handle.id()), &image, layout.bounds()),
+            None => self.draw_invalid(layout.bounds()),
+        }
+    }
+}
+
+impl PancursesRenderer {
+    /// Gets the size in cells of an image of the given size in pixels, drawn with the current
+    /// image protocol
+    pub(super) fn raster_dimensions(&self, size: (u32, u32)) -> (u32, u32) {
+        if self.image_protocol.uses_pixels() {
+            pixel_cell_dimensions(size, self.backend.cell_size())
+        } else {
+            cell_dimensions(size)
+        }
+    }
+
+    /// Gets the size in pixels of an image of the given size, once scaled to fit in the bounds
+    /// with the current image protocol, if it can be drawn
+    pub(super) fn raster_size(&self, size: (u32, u32), bounds: Rectangle) -> Option<(u32, u32)> {
+        let (cell_width, cell_height) = if self.image_protocol.uses_pixels() {
+            self.backend.cell_size()
+        } else {
+            (1, 2)
+        };
+        fit_size(
+            size,
+            bounds.width as u32 * cell_width as u32,
+            bounds.height as u32 * cell_height as u32,
+        )
+    }
+
+    /// Draws an image with the current image protocol, scaled to fit in the given bounds and
+    /// centered in them
+    pub(super) fn draw_raster(
+        &mut self,
+        key: ImageKey,
+        image: &RgbaImage,
+        bounds: Rectangle,
+    ) -> Primitive {
+        match self.image_protocol {
+            ImageProtocol::HalfBlocks => draw_half_blocks(image, bounds),
+            ImageProtocol::Ascii => draw_ascii(image, bounds, &self.ascii_art),
+            ImageProtocol::Sixel | ImageProtocol::Kitty => self.draw_pixels(key, image, bounds),
+        }
+    }
+
+    /// Draws a box in place of an image that cannot be decoded
+    pub(super) fn draw_invalid(&mut self, bounds: Rectangle) -> Primitive {
+        // The text is kept inside of the border
+        let text_bounds = Rectangle {
+            x: bounds.x + 1.,
+            y: bounds.y + 1.,
+            width: (bounds.width - 2.).max(0.),
+            height: (bounds.height - 2.).max(0.),
+        };
         let prim_text = <Self as iced_native::widget::text::Renderer>::draw(
             self,
-            bounds,
-            "Unsupported",
+            text_bounds,
+            "Invalid image",
             1,
             Font::Default,
             None,
             HorizontalAlignment::Center,
             VerticalAlignment::Center,
         );
-        Primitive::Group(vec![Primitive::BoxDisplay(layout.bounds()), prim_text])
+        Primitive::Group(vec![
+            Primitive::BoxDisplay(bounds, Style::default()),
+            prim_text,
+        ])
+    }
+
+    /// Draws an image with real pixels, scaled to fit in the given bounds and centered in them.
+    /// The aspect ratio of the image is preserved.
+    fn draw_pixels(&mut self, key: ImageKey, image: &RgbaImage, bounds: Rectangle) -> Primitive {
+        let cell_size = self.backend.cell_size();
+        let size = match self.raster_size(image.dimensions(), bounds) {
+            Some(size) => size,
+            None => return Primitive::Empty,
+        };
+        let sequence = self
+            .images
+            .borrow_mut()
+            .sequence(key, image, self.image_protocol, size);
+        let (columns, rows) = pixel_cell_dimensions(size, cell_size);
+        let (x, y) = centered(bounds, (columns, rows));
+        Primitive::Graphics(
+            Rectangle {
+                x: x as f32,
+                y: y as f32,
+                width: columns as f32,
+                height: rows as f32,
+            },
+            sequence,
+        )
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::{draw_half_blocks, AsciiArt, ImageKey};
+    use crate::{ImageProtocol, PancursesRenderer};
+    use ::image::{DynamicImage, ImageOutputFormat, Rgba, RgbaImage};
+    use iced_native::widget::image;
+    use iced_native::{Color, Rectangle};
+
+    /// A 2x2 image: red on top of blue in the first column, green on top of a transparent pixel
+    /// in the second one
+    fn sprite() -> RgbaImage {
+        let mut sprite = RgbaImage::new(2, 2);
+        sprite.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        sprite.put_pixel(0, 1, Rgba([0, 0, 255, 255]));
+        sprite.put_pixel(1, 0, Rgba([0, 255, 0, 255]));
+        sprite
+    }
+
+    #[test]
+    pub fn image_should_be_measured_in_cells() {
+        let mut png = Vec::new();
+        DynamicImage::ImageRgba8(sprite())
+            .write_to(&mut png, ImageOutputFormat::Png)
+            .unwrap();
+        let renderer = PancursesRenderer::headless(1, 1);
+        let handle = image::Handle::from_memory(png);
+        assert_eq!(image::Renderer::dimensions(&renderer, &handle), (2, 1));
+    }
+
+    #[test]
+    pub fn image_should_be_drawn_with_pixels() {
+        let mut png = Vec::new();
+        DynamicImage::ImageRgba8(RgbaImage::new(20, 20))
+            .write_to(&mut png, ImageOutputFormat::Png)
+            .unwrap();
+        let handle = image::Handle::from_memory(png);
+        let key = ImageKey::Image(handle.id());
+        let mut renderer = PancursesRenderer::headless(4, 2).image_protocol(ImageProtocol::Kitty);
+        // Cells are 10x20 pixels large by default
+        assert_eq!(image::Renderer::dimensions(&renderer, &handle), (2, 1));
+
+        let sprite = RgbaImage::new(20, 20);
+        let bounds = Rectangle {
+            x: 0.,
+            y: 0.,
+            width: 4.,
+            height: 2.,
+        };
+        let primitive = renderer.draw_pixels(key, &sprite, bounds);
+        renderer.draw(primitive);
+        // The image is scaled to fit in the bounds, that is 40x40 pixels, and centered
+        let writes = renderer.headless_backend().unwrap().raw_writes().to_vec();
+        assert_eq!(writes.len(), 1);
+        assert_eq!((writes[0].0, writes[0].1), (0, 0));
+        assert!(writes[0].2.starts_with("\x1b_Ga=T,f=32,s=40,v=40,"));
+
+        // Unchanged images are not drawn again
+        let primitive = renderer.draw_pixels(key, &sprite, bounds);
+        renderer.draw(primitive);
+        assert_eq!(renderer.headless_backend().unwrap().raw_writes().len(), 1);
+    }
+
+    #[test]
+    pub fn invalid_image_should_be_drawn_inside_border() {
+        let mut renderer = PancursesRenderer::headless(15, 3);
+        let primitive = renderer.draw_invalid(Rectangle {
+            x: 0.,
+            y: 0.,
+            width: 15.,
+            height: 3.,
+        });
+        renderer.draw(primitive);
+        assert_eq!(
+            renderer.headless_backend().unwrap().lines(),
+            vec!["┌─────────────┐", "│Invalid image│", "└─────────────┘"]
+        );
+    }
+
+    #[test]
+    pub fn image_should_draw_half_blocks() {
+        let mut renderer = PancursesRenderer::headless(4, 1);
+        // The image keeps its size, and is centered
+        renderer.draw(draw_half_blocks(
+            &sprite(),
+            Rectangle {
+                x: 0.,
+                y: 0.,
+                width: 4.,
+                height: 1.,
+            },
+        ));
+        let backend = renderer.headless_backend().unwrap();
+        assert_eq!(backend.lines(), vec![" ▀▀ "]);
+        let red = Color::from_rgb(1., 0., 0.);
+        let blue = Color::from_rgb(0., 0., 1.);
+        let green = Color::from_rgb(0., 1., 0.);
+        assert_eq!(backend.cell(1, 0).unwrap().style.foreground, red);
+        assert_eq!(backend.cell(1, 0).unwrap().style.background, Some(blue));
+        assert_eq!(backend.cell(2, 0).unwrap().style.foreground, green);
+        assert_eq!(backend.cell(2, 0).unwrap().style.background, None);
+    }
+
+    #[test]
+    pub fn ascii_art_should_map_luminance() {
+        let gray = |value| Rgba([value, value, value, 255]);
+        let mut gradient = RgbaImage::new(4, 1);
+        for (x, value) in [0, 85, 170, 255].iter().enumerate() {
+            gradient.put_pixel(x as u32, 0, gray(*value));
+        }
+        gradient.put_pixel(3, 0, Rgba([255, 255, 255, 0]));
+        let line = |ascii_art: &AsciiArt, image: &RgbaImage| -> String {
+            ascii_art.convert(image)[0]
+                .iter()
+                .map(|cell| cell.map(|(content, _)| content).unwrap_or('_'))
+                .collect()
+        };
+        // Transparent pixels are left empty
+        assert_eq!(line(&AsciiArt::new().ramp(" .#@"), &gradient), " .#_");
+
+        // A mid gray is drawn as dense, or as one character out of two with dithering
+        let half = RgbaImage::from_pixel(4, 1, gray(128));
+        assert_eq!(line(&AsciiArt::new().ramp(" #"), &half), "####");
+        let dithered = AsciiArt::new().ramp(" #").dithering(true);
+        assert_eq!(line(&dithered, &half), "# # ");
     }
 }
@@ -2,49 +2,90 @@ mod button;
 mod checkbox;
 mod column;
 //mod debugger;
+mod graphics;
 mod image;
 mod radio;
 mod row;
 mod scrollable;
 mod slider;
 mod space;
+mod svg;
 mod text;
-//mod text_input;
+mod text_input;
 
-use crate::colors::{ColorRegistry, PancursesColor};
+pub use self::graphics::ImageProtocol;
+pub use self::image::AsciiArt;
+pub use self::text::WrapMode;
+
+use crate::backend::{Backend, HeadlessBackend, PancursesBackend};
+use crate::focus::FocusRing;
+use crate::frame::{Cell, Frame};
 use crate::primitive::Primitive;
-use iced_native::input::{
-    keyboard, keyboard::KeyCode, mouse::Button, mouse::Event as MouseEvent, ButtonState,
-};
+use crate::style::Style;
 use iced_native::layout::Limits;
-use iced_native::{Event, Renderer};
-use pancurses::{initscr, Input, Window};
+use iced_native::{Color, Event, Rectangle, Renderer};
+use std::cell::RefCell;
+use std::rc::Rc;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 /// Pancurses Renderer implementation for iced
 pub struct PancursesRenderer {
-    /// Pancurses window to use to print UI elements
-    window: Window,
-    /// The ColorRegistry is the place to store pancurses color pairs indices
-    color_registry: ColorRegistry,
+    /// Backend in which UI elements are printed, and from which events are polled
+    backend: Box<dyn Backend>,
+    /// Frame being drawn
+    frame: Frame,
+    /// Frame currently displayed by the backend, if it is known
+    displayed: Option<Frame>,
+    /// Area outside of which nothing is drawn, if any
+    clip: Option<Rectangle>,
+    /// Position of the cursor in the frame being drawn, if any
+    cursor: Option<(u16, u16)>,
+    /// Widgets that can be focused using the keyboard
+    focus: FocusRing,
+    /// How text lines that are too long are displayed
+    wrap_mode: WrapMode,
+    /// Whether texts too tall for their bounds end with an ellipsis
+    ellipsis: bool,
+    /// Images decoded so far, which are measured while computing the layout
+    images: RefCell<image::ImageCache>,
+    /// Vector graphics parsed so far, which are measured while computing the layout
+    svgs: RefCell<svg::SvgCache>,
+    /// How images are drawn
+    image_protocol: ImageProtocol,
+    /// How images are drawn with ASCII characters
+    ascii_art: AsciiArt,
+    /// Images drawn with escape sequences in the frame being drawn
+    graphics: Vec<Graphic>,
+    /// Images drawn with escape sequences currently displayed by the backend
+    displayed_graphics: Vec<Graphic>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// An image drawn by the terminal from an escape sequence
+struct Graphic {
+    x: u16,
+    y: u16,
+    sequence: Rc<str>,
 }
 
 impl Default for PancursesRenderer {
-    /// Default config for a Pancurses renderer
+    /// Default config for a Pancurses renderer, printing to the terminal.
+    /// Images are drawn with the best protocol the terminal seems to support.
     fn default() -> Self {
-        let window = initscr();
-        pancurses::noecho();
-        pancurses::curs_set(0);
-        pancurses::start_color();
-        pancurses::use_default_colors();
-        // Set keypad mode; necessary for correct input handling
-        window.keypad(true);
-
-        // Listen to all mouse events
-        pancurses::mousemask(pancurses::ALL_MOUSE_EVENTS, std::ptr::null_mut());
-        Self {
-            window,
-            color_registry: Default::default(),
-        }
+        Self::new(PancursesBackend::default()).image_protocol(ImageProtocol::detect())
+    }
+}
+
+/// Gets the area shared by two rectangles, which is empty if they do not overlap
+pub(crate) fn intersection(a: Rectangle, b: Rectangle) -> Rectangle {
+    let x = a.x.max(b.x);
+    let y = a.y.max(b.y);
+    Rectangle {
+        x,
+        y,
+        width: ((a.x + a.width).min(b.x + b.width) - x).max(0.),
+        height: ((a.y + a.height).min(b.y + b.height) - y).max(0.),
     }
 }
 
@@ -55,178 +96,331 @@ impl Renderer for PancursesRenderer {
         &mut self,
         element: &iced_native::Element<'a, Message, Self>,
     ) -> iced_native::layout::Node {
+        let (width, height) = self.backend.size();
         let limits = Limits::NONE
-            .max_width(self.window.get_max_x() as u32)
-            .max_height(self.window.get_max_y() as u32);
+            .max_width(width as u32)
+            .max_height(height as u32);
         element.layout(self, &limits)
     }
 }
 
 impl PancursesRenderer {
-    /// Clears the output of the renderer
-    pub fn flush(&self) {
-        self.window.clear();
-        self.window.refresh();
-    }
-
-    /// Polls event from the pancurses window
-    pub fn handle(&self) -> Option<Vec<Event>> {
-        let input = self.window.getch();
-        match input {
-            Some(Input::Character(c)) => {
-                Some(vec![Event::Keyboard(keyboard::Event::CharacterReceived(c))])
-            }
-            Some(Input::KeyResize) => { self.flush(); None },
-            Some(Input::KeyBackspace) => Some(vec![
-                Event::Keyboard(keyboard::Event::Input {
-                    state: ButtonState::Pressed,
-                    key_code: KeyCode::Backspace,
-                    modifiers: keyboard::ModifiersState {
-                        shift: false,
-                        control: false,
-                        alt: false,
-                        logo: false,
-                    },
-                }),
-                Event::Keyboard(keyboard::Event::Input {
-                    state: ButtonState::Released,
-                    key_code: KeyCode::Backspace,
-                    modifiers: keyboard::ModifiersState {
-                        shift: false,
-                        control: false,
-                        alt: false,
-                        logo: false,
-                    },
-                }),
-            ]),
-            Some(Input::KeyEnter) => Some(vec![
-                Event::Keyboard(keyboard::Event::Input {
-                    state: ButtonState::Pressed,
-                    key_code: KeyCode::Enter,
-                    modifiers: keyboard::ModifiersState {
-                        shift: false,
-                        control: false,
-                        alt: false,
-                        logo: false,
-                    },
-                }),
-                Event::Keyboard(keyboard::Event::Input {
-                    state: ButtonState::Released,
-                    key_code: KeyCode::Enter,
-                    modifiers: keyboard::ModifiersState {
-                        shift: false,
-                        control: false,
-                        alt: false,
-                        logo: false,
-                    },
-                }),
-            ]),
-            Some(Input::KeyMouse) => {
-                if let Ok(mouse_event) = pancurses::getmouse() {
-                    match mouse_event.bstate {
-                        pancurses::BUTTON1_PRESSED => Some(move_cursor_and(
-                            mouse_event.x,
-                            mouse_event.y,
-                            vec![Event::Mouse(MouseEvent::Input {
-                                state: ButtonState::Pressed,
-                                button: Button::Left,
-                            })],
-                        )),
-                        pancurses::BUTTON1_RELEASED => Some(move_cursor_and(
-                            mouse_event.x,
-                            mouse_event.y,
-                            vec![Event::Mouse(MouseEvent::Input {
-                                state: ButtonState::Released,
-                                button: Button::Left,
-                            })],
-                        )),
-                        pancurses::BUTTON1_CLICKED => Some(move_cursor_and(
-                            mouse_event.x,
-                            mouse_event.y,
-                            vec![
-                                Event::Mouse(MouseEvent::Input {
-                                    state: ButtonState::Pressed,
-                                    button: Button::Left,
-                                }),
-                                Event::Mouse(MouseEvent::Input {
-                                    state: ButtonState::Released,
-                                    button: Button::Left,
-                                }),
-                            ],
-                        )),
-                        _ => None,
-                    }
-                } else {
-                    None
-                }
-            }
-            _ => None,
+    /// Creates a renderer using the given backend
+    pub fn new<B: Backend + 'static>(backend: B) -> Self {
+        PancursesRenderer {
+            backend: Box::new(backend),
+            frame: Frame::new(0, 0),
+            displayed: None,
+            clip: None,
+            cursor: None,
+            focus: Default::default(),
+            wrap_mode: Default::default(),
+            ellipsis: true,
+            images: Default::default(),
+            svgs: Default::default(),
+            image_protocol: Default::default(),
+            ascii_art: Default::default(),
+            graphics: Vec::new(),
+            displayed_graphics: Vec::new(),
         }
     }
 
+    /// Creates a renderer drawing in memory, on a screen of the given size.
+    ///
+    /// Useful to test UIs without a terminal: see `HeadlessBackend`.
+    pub fn headless(width: u16, height: u16) -> Self {
+        Self::new(HeadlessBackend::new(width, height))
+    }
+
+    /// Gets the backend of the renderer
+    pub fn backend(&self) -> &dyn Backend {
+        self.backend.as_ref()
+    }
+
+    /// Gets the backend of the renderer, mutably
+    pub fn backend_mut(&mut self) -> &mut dyn Backend {
+        self.backend.as_mut()
+    }
+
+    /// Gets the backend of the renderer if it is of the given type
+    pub fn backend_as<B: Backend>(&self) -> Option<&B> {
+        self.backend().as_any().downcast_ref()
+    }
+
+    /// Gets the backend of the renderer if it is of the given type, mutably
+    pub fn backend_as_mut<B: Backend>(&mut self) -> Option<&mut B> {
+        self.backend_mut().as_any_mut().downcast_mut()
+    }
+
+    /// Gets the backend of the renderer if it is a `HeadlessBackend`
+    pub fn headless_backend(&self) -> Option<&HeadlessBackend> {
+        self.backend_as()
+    }
+
+    /// Gets the backend of the renderer if it is a `HeadlessBackend`, mutably
+    pub fn headless_backend_mut(&mut self) -> Option<&mut HeadlessBackend> {
+        self.backend_as_mut()
+    }
+
+    /// Clears the output of the renderer. The next frame will be redrawn entirely.
+    pub fn flush(&mut self) {
+        self.displayed = None;
+        self.backend.clear();
+        self.backend.refresh();
+    }
+
+    /// Polls event from the backend, handling keyboard focus navigation
+    pub fn handle(&mut self) -> Option<Vec<Event>> {
+        let focus = &mut self.focus;
+        self.backend
+            .poll_events()
+            .map(|events| focus.handle(events))
+    }
+
     // Sets nodelay to true in order to provide async actions
-    pub fn nodelay(self) -> Self {
-        self.window.nodelay(true);
+    pub fn nodelay(mut self) -> Self {
+        self.backend.set_nodelay(true);
+        self
+    }
+
+    /// Sets how text lines that are too long for their bounds are displayed.
+    /// Lines are wrapped between words by default.
+    pub fn wrap_mode(mut self, wrap_mode: WrapMode) -> Self {
+        self.wrap_mode = wrap_mode;
         self
     }
 
-    /// Draws a given primitive onto the window
+    /// Sets whether texts too tall for their bounds end with an ellipsis on their last
+    /// visible line, which is the case by default
+    pub fn ellipsis(mut self, ellipsis: bool) -> Self {
+        self.ellipsis = ellipsis;
+        self
+    }
+
+    /// Sets how images are drawn. Images are drawn with half blocks by default, and with the
+    /// protocol detected from the environment when printing to the terminal.
+    pub fn image_protocol(mut self, image_protocol: ImageProtocol) -> Self {
+        self.image_protocol = image_protocol;
+        self
+    }
+
+    /// Sets how images are drawn with ASCII characters, when using `ImageProtocol::Ascii`
+    pub fn ascii_art(mut self, ascii_art: AsciiArt) -> Self {
+        self.ascii_art = ascii_art;
+        self
+    }
+
+    /// Draws a given primitive onto the backend
     pub fn draw(&mut self, primitive: Primitive) {
+        // Widget renderers have registered their focusable widgets while producing the primitive
+        self.focus.end_frame();
+        self.cursor = None;
+        let (width, height) = self.backend.size();
+        self.frame = Frame::new(width, height);
+        self.draw_primitive(primitive);
+        self.present();
+        // The cursor is placed last, as printing characters moves it
+        self.backend.set_cursor(self.cursor);
+        self.backend.refresh();
+    }
+
+    /// Prints the cells of the drawn frame that differ from the displayed one.
+    /// The whole frame is printed on a cleared screen when the displayed one is unknown,
+    /// or when the screen has been resized.
+    ///
+    /// Images drawn with escape sequences cannot be partly erased: they are all drawn again
+    /// when one of them changes, on a cleared screen.
+    fn present(&mut self) {
+        let frame = std::mem::replace(&mut self.frame, Frame::new(0, 0));
+        let graphics = std::mem::take(&mut self.graphics);
+        let graphics_changed = graphics != self.displayed_graphics;
+        let erase_graphics = graphics_changed && !self.displayed_graphics.is_empty();
+        let mut repainted = false;
+        let displayed = match self.displayed.take() {
+            Some(displayed) if displayed.size() == frame.size() && !erase_graphics => displayed,
+            _ => {
+                self.backend.clear();
+                repainted = true;
+                let (width, height) = frame.size();
+                Frame::new(width, height)
+            }
+        };
+        for (x, y, cell) in frame.diff(&displayed) {
+            self.backend.put_char(x, y, &cell.content, cell.style);
+        }
+        // Cells left untouched may have been altered while printing, they are printed again
+        if self.backend.take_invalidated() {
+            self.backend.clear();
+            let (width, height) = frame.size();
+            for (x, y, cell) in frame.diff(&Frame::new(width, height)) {
+                self.backend.put_char(x, y, &cell.content, cell.style);
+            }
+            self.backend.take_invalidated();
+            repainted = true;
+        }
+        if repainted || graphics_changed {
+            if let Some(sequence) = self.image_protocol.clear_sequence() {
+                if !self.displayed_graphics.is_empty() {
+                    self.backend.write_raw(0, 0, sequence);
+                }
+            }
+            for graphic in &graphics {
+                self.backend
+                    .write_raw(graphic.x, graphic.y, &graphic.sequence);
+            }
+        }
+        self.displayed = Some(frame);
+        self.displayed_graphics = graphics;
+    }
+
+    fn draw_primitive(&mut self, primitive: Primitive) {
         match primitive {
-            Primitive::Group(prims) => prims.into_iter().for_each(|p| self.draw(p)),
-            Primitive::Text(texts, bounds, color) => {
-                let col = crate::colors::get_closest_color(color);
-                let col_idx = self.color_registry.get_idx(PancursesColor::new(col, -1));
-                self.window
-                    .attrset(pancurses::COLOR_PAIR((col_idx as u32).into()));
-                let mut y = 0;
-                texts.into_iter().for_each(|l| {
-                    self.window.mv(bounds.y as i32 + y as i32, bounds.x as i32);
-                    self.window.addstr(l);
-                    y += 1;
+            Primitive::Group(prims) => prims.into_iter().for_each(|p| self.draw_primitive(p)),
+            Primitive::Text(texts, bounds, style) => {
+                texts.iter().enumerate().for_each(|(y, l)| {
+                    self.put_str(bounds.x as i32, bounds.y as i32 + y as i32, l, style)
                 });
             }
-            Primitive::BoxDisplay(bounds) => {
-                let col_idx = self
-                    .color_registry
-                    .get_idx(PancursesColor::new(pancurses::COLOR_WHITE, -1));
-                self.window
-                    .attrset(pancurses::COLOR_PAIR((col_idx as u32).into()));
-                let x = bounds.x as i32;
-                let y = bounds.y as i32;
-                let w = bounds.width as i32;
-                let h = bounds.height as i32;
-                if let Ok(sub_win) = self.window.subwin(h, w, y, x) {
-                    sub_win.border(0, 0, 0, 0, 0, 0, 0, 0);
-                    sub_win.delwin();
+            Primitive::BoxDisplay(bounds, style) => self.put_box(bounds, style),
+            Primitive::Quad(bounds, background, border) => {
+                self.fill(bounds, background);
+                if let Some(style) = border {
+                    self.put_box(bounds, style);
                 }
             }
-            Primitive::Char(x, y, boxchar) => {
-                let col_idx = self
-                    .color_registry
-                    .get_idx(PancursesColor::new(pancurses::COLOR_WHITE, -1));
-                self.window
-                    .attrset(pancurses::COLOR_PAIR((col_idx as u32).into()));
-                self.window.mv(y, x);
-                self.window.addch(boxchar);
+            Primitive::Char(x, y, content, style) => self.put_char(x, y, content, style),
+            Primitive::Cursor(x, y) if self.is_drawable(x, y) => {
+                self.cursor = Some((x as u16, y as u16));
+            }
+            Primitive::Clip(bounds, content) => {
+                // Nested clips only draw in the intersection of their areas
+                let previous = self.clip;
+                self.clip = Some(match previous {
+                    Some(clip) => intersection(clip, bounds),
+                    None => bounds,
+                });
+                self.draw_primitive(*content);
+                self.clip = previous;
+            }
+            Primitive::Graphics(bounds, sequence) => {
+                // The cells under the image are blanked, the image being drawn over them
+                let (x, y) = (bounds.x as i32, bounds.y as i32);
+                let (right, bottom) = (x + bounds.width as i32 - 1, y + bounds.height as i32 - 1);
+                for j in y..=bottom {
+                    for i in x..=right {
+                        self.put_char(i, j, ' ', Style::default());
+                    }
+                }
+                // Escape sequences cannot be clipped: images partly hidden are not drawn
+                if self.is_drawable(x, y) && self.is_drawable(right, bottom) {
+                    self.graphics.push(Graphic {
+                        x: x as u16,
+                        y: y as u16,
+                        sequence,
+                    });
+                }
             }
             _ => (),
         }
     }
 
-    /// Gets the current size of the terminal root window
-    pub fn size(&self) -> (u16, u16) {
-        let yx = self.window.get_max_yx();
-        (yx.1 as u16, yx.0 as u16)
+    /// Returns whether the given position is on the screen, and inside of the clipping area
+    fn is_drawable(&self, x: i32, y: i32) -> bool {
+        let (width, height) = self.frame.size();
+        let on_screen = x >= 0 && y >= 0 && x < width as i32 && y < height as i32;
+        let in_clip = match self.clip {
+            Some(clip) => {
+                x >= clip.x as i32
+                    && y >= clip.y as i32
+                    && x < (clip.x + clip.width) as i32
+                    && y < (clip.y + clip.height) as i32
+            }
+            None => true,
+        };
+        on_screen && in_clip
+    }
+
+    /// Prints a character in the frame, ignoring it if it is outside of the screen
+    /// or of the clipping area
+    fn put_char(&mut self, x: i32, y: i32, content: char, style: Style) {
+        self.put_grapheme(x, y, content.encode_utf8(&mut [0; 4]), style);
+    }
+
+    /// Prints a grapheme cluster in the frame, ignoring it if it is outside of the screen
+    /// or of the clipping area. Returns the number of columns it takes.
+    ///
+    /// Without a background, the grapheme keeps the background of the cell.
+    /// Wide graphemes that do not fit are replaced by spaces, on their drawable columns.
+    fn put_grapheme(&mut self, x: i32, y: i32, grapheme: &str, style: Style) -> i32 {
+        let width = grapheme.width() as i32;
+        if width == 0 {
+            return 0;
+        }
+        if width > 1 && (0..width).any(|i| !self.is_drawable(x + i, y)) {
+            (0..width).for_each(|i| self.set_cell(x + i, y, " ".into(), style));
+        } else {
+            self.set_cell(x, y, grapheme.into(), style);
+            (1..width).for_each(|i| self.set_cell(x + i, y, String::new(), style));
+        }
+        width
     }
-}
 
-pub fn move_cursor_and(x: i32, y: i32, other: Vec<Event>) -> Vec<Event> {
-    vec![Event::Mouse(MouseEvent::CursorMoved {
-        x: x as f32,
-        y: y as f32,
-    })]
-    .into_iter()
-    .chain(other.into_iter())
-    .collect()
+    /// Sets the content of a cell of the frame, if it is drawable
+    fn set_cell(&mut self, x: i32, y: i32, content: String, mut style: Style) {
+        if self.is_drawable(x, y) {
+            let (x, y) = (x as u16, y as u16);
+            if style.background.is_none() {
+                style.background = self.frame.get(x, y).and_then(|cell| cell.style.background);
+            }
+            self.frame.set(x, y, Cell { content, style });
+        }
+    }
+
+    /// Fills an area with blank cells of the given background color
+    fn fill(&mut self, bounds: Rectangle, background: Color) {
+        let style = Style::default().background(background);
+        let (x, y) = (bounds.x as i32, bounds.y as i32);
+        for j in y..y + bounds.height as i32 {
+            for i in x..x + bounds.width as i32 {
+                self.put_char(i, j, ' ', style);
+            }
+        }
+    }
+
+    /// Prints a line of text starting at the given position
+    fn put_str(&mut self, x: i32, y: i32, content: &str, style: Style) {
+        let mut column = x;
+        for grapheme in content.graphemes(true) {
+            column += self.put_grapheme(column, y, grapheme, style);
+        }
+    }
+
+    /// Prints the border of a box
+    fn put_box(&mut self, bounds: Rectangle, style: Style) {
+        let x = bounds.x as i32;
+        let y = bounds.y as i32;
+        let w = bounds.width as i32;
+        let h = bounds.height as i32;
+        if w < 2 || h < 2 {
+            return;
+        }
+        let (right, bottom) = (x + w - 1, y + h - 1);
+        (x + 1..right).for_each(|i| {
+            self.put_char(i, y, '─', style);
+            self.put_char(i, bottom, '─', style);
+        });
+        (y + 1..bottom).for_each(|j| {
+            self.put_char(x, j, '│', style);
+            self.put_char(right, j, '│', style);
+        });
+        self.put_char(x, y, '┌', style);
+        self.put_char(right, y, '┐', style);
+        self.put_char(x, bottom, '└', style);
+        self.put_char(right, bottom, '┘', style);
+    }
+
+    /// Gets the current size of the screen
+    pub fn size(&self) -> (u16, u16) {
+        self.backend.size()
+    }
 }
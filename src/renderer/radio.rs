@@ -1,4 +1,6 @@
+use crate::focus::{FocusKind, FOCUS_COLOR};
 use crate::primitive::Primitive;
+use crate::style::Style;
 use crate::PancursesRenderer;
 use iced_native::widget::radio::Renderer as RadioRenderer;
 use iced_native::Rectangle;
@@ -16,9 +18,27 @@ impl RadioRenderer for PancursesRenderer {
         label: Primitive,
     ) -> Primitive {
         let radiochar = if is_selected { 'x' } else { 'o' };
-        Primitive::Group(vec![
-            Primitive::Char(bounds.x as i32, bounds.y as i32, radiochar),
-            label,
-        ])
+        // The focused radio is highlighted by coloring its indicator and its label
+        if self.focus.register(bounds, FocusKind::Radio) {
+            Primitive::Group(vec![
+                Primitive::Char(
+                    bounds.x as i32,
+                    bounds.y as i32,
+                    radiochar,
+                    Style::new(FOCUS_COLOR),
+                ),
+                label.with_color(FOCUS_COLOR),
+            ])
+        } else {
+            Primitive::Group(vec![
+                Primitive::Char(
+                    bounds.x as i32,
+                    bounds.y as i32,
+                    radiochar,
+                    Style::default(),
+                ),
+                label,
+            ])
+        }
     }
 }
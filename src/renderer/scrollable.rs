@@ -1,33 +1,203 @@
+use crate::focus::FOCUS_COLOR;
 use crate::primitive::Primitive;
+use crate::style::Style;
 use crate::PancursesRenderer;
 
 use iced_native::widget::scrollable;
 use iced_native::Rectangle;
 
+/// Character drawing the track of the scrollbar
+const TRACK_CHAR: char = '│';
+/// Character drawing the thumb of the scrollbar
+const THUMB_CHAR: char = '█';
+
 impl scrollable::Renderer for PancursesRenderer {
     fn scrollbar(
         &self,
-        _bounds: Rectangle,
-        _content_bounds: Rectangle,
-        _offset: u32,
+        bounds: Rectangle,
+        content_bounds: Rectangle,
+        offset: u32,
     ) -> Option<scrollable::Scrollbar> {
-        None
+        // The track has to fit between the top and bottom sides of the border
+        if content_bounds.height <= bounds.height || bounds.width < 1. || bounds.height < 3. {
+            return None;
+        }
+        // The track replaces the right side of the border, between its corners
+        let track = Rectangle {
+            x: bounds.x + bounds.width - 1.,
+            y: bounds.y + 1.,
+            width: 1.,
+            height: bounds.height - 2.,
+        };
+        // The thumb is as tall, relatively to the track, as the visible part of the content
+        let thumb_height = (track.height * bounds.height / content_bounds.height)
+            .round()
+            .max(1.)
+            .min(track.height);
+        let scrolled = offset as f32 / (content_bounds.height - bounds.height);
+        let thumb = Rectangle {
+            y: track.y + ((track.height - thumb_height) * scrolled.min(1.)).round(),
+            height: thumb_height,
+            ..track
+        };
+        Some(scrollable::Scrollbar {
+            bounds: track,
+            scroller: scrollable::Scroller { bounds: thumb },
+        })
     }
 
     fn draw(
         &mut self,
-        _scrollable: &scrollable::State,
+        scrollable: &scrollable::State,
         bounds: Rectangle,
-        _content_bounds: Rectangle,
+        content_bounds: Rectangle,
         _is_mouse_over: bool,
-        _is_mouse_over_scrollbar: bool,
-        _scrollbar: Option<scrollable::Scrollbar>,
+        is_mouse_over_scrollbar: bool,
+        scrollbar: Option<scrollable::Scrollbar>,
         offset: u32,
         content: Self::Output,
     ) -> Primitive {
-        Primitive::Group(vec![
-            Primitive::BoxDisplay(bounds),
-            content.with_offset(offset as i32),
-        ])
+        // The focused scrollable is highlighted by coloring its border
+        let is_focused = self
+            .focus
+            .register_scrollable(bounds, content_bounds, offset as f32);
+        let border_style = if is_focused {
+            Style::new(FOCUS_COLOR)
+        } else {
+            Style::default()
+        };
+        let mut primitives = vec![
+            // The content is laid out from the corner of the scrollable: it is drawn over
+            // the border, which only shows where the content leaves it uncovered
+            Primitive::BoxDisplay(bounds, border_style),
+            // Scrolled content is hidden once out of the scrollable
+            Primitive::Clip(bounds, Box::new(content.with_offset(offset as i32))),
+        ];
+        if let Some(scrollbar) = scrollbar {
+            // The thumb is highlighted while it can be grabbed, or while it is dragged
+            let thumb_style = if is_mouse_over_scrollbar || scrollable.is_scroller_grabbed() {
+                Style::new(FOCUS_COLOR)
+            } else {
+                Style::default()
+            };
+            primitives.push(column(scrollbar.bounds, TRACK_CHAR, Style::default().dim()));
+            primitives.push(column(scrollbar.scroller.bounds, THUMB_CHAR, thumb_style));
+        }
+        Primitive::Group(primitives)
+    }
+}
+
+/// Creates the primitive filling a one column wide area with the given character
+fn column(bounds: Rectangle, content: char, style: Style) -> Primitive {
+    Primitive::Group(
+        (0..bounds.height as i32)
+            .map(|y| Primitive::Char(bounds.x as i32, bounds.y as i32 + y, content, style))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use crate::primitive::Primitive;
+    use crate::style::Style;
+    use crate::PancursesRenderer;
+    use iced_native::widget::scrollable;
+    use iced_native::Rectangle;
+
+    #[test]
+    pub fn scrollbar_should_follow_offset() {
+        let mut renderer = PancursesRenderer::headless(4, 4);
+        let bounds = Rectangle {
+            x: 0.,
+            y: 0.,
+            width: 4.,
+            height: 4.,
+        };
+        let content_bounds = Rectangle {
+            height: 8.,
+            ..bounds
+        };
+        // Content fitting in the scrollable has no scrollbar
+        assert!(scrollable::Renderer::scrollbar(&renderer, bounds, bounds, 0).is_none());
+
+        // Half of the content is visible: the thumb takes half of the track
+        let scrollbar = scrollable::Renderer::scrollbar(&renderer, bounds, content_bounds, 4)
+            .expect("The content overflows");
+        assert_eq!(scrollbar.bounds.x, 3.);
+        assert_eq!(scrollbar.bounds.y, 1.);
+        assert_eq!(scrollbar.bounds.height, 2.);
+        assert_eq!(scrollbar.scroller.bounds.y, 2.);
+        assert_eq!(scrollbar.scroller.bounds.height, 1.);
+
+        let primitive = scrollable::Renderer::draw(
+            &mut renderer,
+            &scrollable::State::new(),
+            bounds,
+            content_bounds,
+            false,
+            false,
+            Some(scrollbar),
+            4,
+            Primitive::Empty,
+        );
+        renderer.draw(primitive);
+        // Scrolled to the end, the scrollbar looks like this, the corners being kept:
+        //
+        // ┌──┐
+        // │  │
+        // │  █
+        // └──┘
+        assert_eq!(
+            renderer.headless_backend().unwrap().lines(),
+            vec!["┌──┐", "│  │", "│  █", "└──┘"]
+        );
+
+        // Without room between the corners, there is no scrollbar
+        let flat = Rectangle {
+            height: 2.,
+            ..bounds
+        };
+        assert!(scrollable::Renderer::scrollbar(&renderer, flat, content_bounds, 0).is_none());
+    }
+
+    #[test]
+    pub fn scrollable_should_clip_content_to_its_bounds() {
+        let mut renderer = PancursesRenderer::headless(4, 5);
+        let bounds = Rectangle {
+            x: 0.,
+            y: 0.,
+            width: 4.,
+            height: 4.,
+        };
+        let content_bounds = Rectangle {
+            height: 6.,
+            ..bounds
+        };
+        let content = Primitive::Text(
+            (0..6).map(|line| format!("{}{}", line, line)).collect(),
+            Rectangle {
+                width: 2.,
+                ..content_bounds
+            },
+            Style::default(),
+        );
+        // Scrolled by a line, the lines 1 to 4 of the content are shown, over the border
+        let primitive = scrollable::Renderer::draw(
+            &mut renderer,
+            &scrollable::State::new(),
+            bounds,
+            content_bounds,
+            false,
+            false,
+            None,
+            1,
+            content,
+        );
+        renderer.draw(primitive);
+        assert_eq!(
+            renderer.headless_backend().unwrap().lines(),
+            vec!["11─┐", "22 │", "33 │", "44─┘", "    "]
+        );
     }
 }
@@ -1,4 +1,6 @@
+use crate::focus::FOCUS_COLOR;
 use crate::primitive::Primitive;
+use crate::style::Style;
 use crate::PancursesRenderer;
 
 use iced_native::widget::slider;
@@ -6,6 +8,35 @@ use iced_native::{Point, Rectangle};
 
 use std::ops::RangeInclusive;
 
+/// Character of the part of the track after the handle
+const TRACK_CHAR: char = '─';
+/// Character of the part of the track before the handle
+const FILL_CHAR: char = '━';
+/// Character of the handle
+const HANDLE_CHAR: char = '●';
+
+/// Gets the position of a value in a range, from 0 at its start to 1 at its end.
+/// Values outside of the range are clamped, and empty ranges put every value at their start.
+pub fn range_position(range: &RangeInclusive<f32>, value: f32) -> f32 {
+    let (start, end) = (*range.start(), *range.end());
+    let position = (value - start) / (end - start);
+    if position.is_finite() {
+        position.clamp(0., 1.)
+    } else {
+        0.
+    }
+}
+
+/// Gets the column of the handle of a slider of the given width, from its position in its range.
+///
+/// Iced sets the value of a slider to the position of the cursor in its bounds: clicking on the
+/// column `n` moves the handle there, and the end of the range is only reached on the right of the
+/// last column, where the handle stays.
+/// Rounding errors of the value computed by iced are tolerated.
+pub fn handle_column(position: f32, width: u32) -> u32 {
+    ((position * width as f32 + 0.001).floor() as u32).min(width.max(1) - 1)
+}
+
 impl slider::Renderer for PancursesRenderer {
     fn height(&self) -> u32 {
         1
@@ -14,18 +45,103 @@ impl slider::Renderer for PancursesRenderer {
     fn draw(
         &mut self,
         bounds: Rectangle,
-        _cursor_position: Point,
+        cursor_position: Point,
         range: RangeInclusive<f32>,
         value: f32,
-        _is_dragging: bool,
+        is_dragging: bool,
     ) -> Primitive {
-        let (range_start, range_end) = range.into_inner();
-        let marker_offset =
-            bounds.width * ((value - range_start) / (range_end - range_start).max(1.0));
+        let width = bounds.width as u32;
+        if width == 0 {
+            return Primitive::Empty;
+        }
+        let position = range_position(&range, value);
+        let handle = handle_column(position, width);
 
+        // The focused slider is highlighted by coloring its handle
+        let mut handle_style = if self.focus.register_slider(bounds, position) {
+            Style::new(FOCUS_COLOR)
+        } else {
+            Style::default()
+        };
+        if is_dragging || bounds.contains(cursor_position) {
+            handle_style = handle_style.bold();
+        }
+        if is_dragging {
+            handle_style = handle_style.reverse();
+        }
+
+        let line = |x: u32, length: u32, content: char, style: Style| {
+            Primitive::Text(
+                vec![content.to_string().repeat(length as usize)],
+                Rectangle {
+                    x: bounds.x + x as f32,
+                    y: bounds.y,
+                    width: length as f32,
+                    height: 1.,
+                },
+                style,
+            )
+        };
         Primitive::Group(vec![
-            Primitive::BoxDisplay(bounds),
-            Primitive::Char(bounds.x as i32 + marker_offset as i32, bounds.y as i32, 'x'),
+            line(0, handle, FILL_CHAR, Style::default()),
+            line(
+                handle + 1,
+                width - handle - 1,
+                TRACK_CHAR,
+                Style::default().dim(),
+            ),
+            Primitive::Char(
+                (bounds.x + handle as f32) as i32,
+                bounds.y as i32,
+                HANDLE_CHAR,
+                handle_style,
+            ),
         ])
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::{handle_column, range_position};
+    use crate::PancursesRenderer;
+    use iced_native::widget::slider;
+    use iced_native::{Point, Rectangle};
+
+    #[test]
+    pub fn slider_handle_should_stay_on_track() {
+        // Narrow ranges are not stretched, and values outside of the range are clamped
+        assert_eq!(range_position(&(0.0..=0.5), 0.25), 0.5);
+        assert_eq!(range_position(&(-1.0..=1.0), 3.), 1.);
+        assert_eq!(range_position(&(1.0..=1.0), 1.), 0.);
+
+        // The handle is on the column clicked to set the value, the last one at the end
+        assert_eq!(handle_column(0., 10), 0);
+        assert_eq!(handle_column(0.3, 10), 3);
+        assert_eq!(handle_column(1., 10), 9);
+    }
+
+    #[test]
+    pub fn slider_should_draw_track() {
+        let mut renderer = PancursesRenderer::headless(10, 1);
+        let bounds = Rectangle {
+            x: 0.,
+            y: 0.,
+            width: 10.,
+            height: 1.,
+        };
+        let primitive = slider::Renderer::draw(
+            &mut renderer,
+            bounds,
+            Point::new(-1., -1.),
+            0.0..=0.1,
+            0.04,
+            true,
+        );
+        renderer.draw(primitive);
+        let backend = renderer.headless_backend().unwrap();
+        assert_eq!(backend.lines(), vec!["━━━━●─────"]);
+        assert!(backend.cell(4, 0).unwrap().style.reverse);
+        assert!(backend.cell(5, 0).unwrap().style.dim);
+    }
+}
@@ -0,0 +1,158 @@
+use super::image::{ImageKey, INVALID_DIMENSIONS};
+use crate::primitive::Primitive;
+use crate::PancursesRenderer;
+use ::image::RgbaImage;
+use iced_native::widget::svg;
+use iced_native::{Layout, Rectangle};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Parsed vector graphics, by id of their handle.
+/// Graphics that could not be parsed are kept as well, to avoid parsing them again.
+///
+/// The last rasterization of each graphic is kept too, with its size.
+#[derive(Default)]
+pub struct SvgCache {
+    trees: HashMap<u64, Option<Rc<usvg::Tree>>>,
+    rasters: HashMap<u64, Rc<RgbaImage>>,
+}
+
+impl SvgCache {
+    /// Gets the parsed graphic of a handle, parsing it if it is not in the cache
+    fn get(&mut self, handle: &svg::Handle) -> Option<Rc<usvg::Tree>> {
+        self.trees
+            .entry(handle.id())
+            .or_insert_with(|| parse(handle).map(Rc::new))
+            .clone()
+    }
+
+    /// Gets a graphic rasterized at the given size, rasterizing it if it has not been
+    /// at this size
+    fn raster(
+        &mut self,
+        handle: &svg::Handle,
+        tree: &usvg::Tree,
+        size: (u32, u32),
+    ) -> Option<Rc<RgbaImage>> {
+        match self.rasters.get(&handle.id()) {
+            Some(raster) if raster.dimensions() == size => Some(raster.clone()),
+            _ => {
+                let raster = Rc::new(rasterize(tree, size)?);
+                self.rasters.insert(handle.id(), raster.clone());
+                Some(raster)
+            }
+        }
+    }
+}
+
+/// Parses the graphic of a handle, from its file.
+/// Resources the graphic links to are looked for next to the file.
+fn parse(handle: &svg::Handle) -> Option<usvg::Tree> {
+    let data = std::fs::read(handle.path()).ok()?;
+    let options = usvg::Options {
+        resources_dir: handle.path().parent().map(|dir| dir.to_path_buf()),
+        ..usvg::Options::default()
+    };
+    usvg::Tree::from_data(&data, &options.to_ref()).ok()
+}
+
+/// Gets the size of a graphic in pixels, as it is defined by its `width` and `height`
+fn tree_size(tree: &usvg::Tree) -> (u32, u32) {
+    let size = tree.svg_node().size;
+    (
+        size.width().ceil().max(1.) as u32,
+        size.height().ceil().max(1.) as u32,
+    )
+}
+
+/// Renders a graphic to an image of the given size
+fn rasterize(tree: &usvg::Tree, (width, height): (u32, u32)) -> Option<RgbaImage> {
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)?;
+    resvg::render(
+        tree,
+        usvg::FitTo::Size(width, height),
+        tiny_skia::Transform::default(),
+        pixmap.as_mut(),
+    )?;
+    // Pixels are rendered with their colors premultiplied by their alpha
+    let mut data = Vec::with_capacity(pixmap.pixels().len() * 4);
+    for pixel in pixmap.pixels() {
+        let color = pixel.demultiply();
+        data.extend_from_slice(&[color.red(), color.green(), color.blue(), color.alpha()]);
+    }
+    RgbaImage::from_raw(width, height, data)
+}
+
+impl svg::Renderer for PancursesRenderer {
+    fn dimensions(&self, handle: &svg::Handle) -> (u32, u32) {
+        match self.svgs.borrow_mut().get(handle) {
+            Some(tree) => self.raster_dimensions(tree_size(&tree)),
+            None => INVALID_DIMENSIONS,
+        }
+    }
+
+    fn draw(&mut self, handle: svg::Handle, layout: Layout) -> Primitive {
+        self.draw_svg(&handle, layout.bounds())
+    }
+}
+
+impl PancursesRenderer {
+    /// Draws a graphic like an image, rasterized at the size it takes in the given bounds
+    fn draw_svg(&mut self, handle: &svg::Handle, bounds: Rectangle) -> Primitive {
+        let tree = self.svgs.borrow_mut().get(handle);
+        let tree = match tree {
+            Some(tree) => tree,
+            None => return self.draw_invalid(bounds),
+        };
+        let raster = self
+            .raster_size(tree_size(&tree), bounds)
+            .and_then(|size| self.svgs.borrow_mut().raster(handle, &tree, size));
+        match raster {
+            Some(raster) => self.draw_raster(ImageKey::Svg(handle.id()), &raster, bounds),
+            None => Primitive::Empty,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use crate::PancursesRenderer;
+    use iced_native::widget::svg;
+    use iced_native::{Color, Rectangle};
+
+    #[test]
+    pub fn svg_should_be_rasterized() {
+        let path = std::env::temp_dir().join("iced_pancurses_svg_should_be_rasterized.svg");
+        std::fs::write(
+            &path,
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="8">
+                <rect width="8" height="4" fill="#ff0000"/>
+                <rect y="4" width="8" height="4" fill="#0000ff"/>
+            </svg>"##,
+        )
+        .unwrap();
+        let handle = svg::Handle::from_path(&path);
+        let mut renderer = PancursesRenderer::headless(2, 1);
+        // Each pixel is a column wide, and half a line tall
+        assert_eq!(svg::Renderer::dimensions(&renderer, &handle), (8, 4));
+
+        // The graphic is rendered at the size it is drawn, that is two pixels by two
+        let primitive = renderer.draw_svg(
+            &handle,
+            Rectangle {
+                x: 0.,
+                y: 0.,
+                width: 2.,
+                height: 1.,
+            },
+        );
+        renderer.draw(primitive);
+        std::fs::remove_file(&path).unwrap();
+        let backend = renderer.headless_backend().unwrap();
+        assert_eq!(backend.lines(), vec!["▀▀"]);
+        let cell = backend.cell(0, 0).unwrap();
+        assert_eq!(cell.style.foreground, Color::from_rgb(1., 0., 0.));
+        assert_eq!(cell.style.background, Some(Color::from_rgb(0., 0., 1.)));
+    }
+}
@@ -1,9 +1,43 @@
 use crate::primitive::Primitive;
 use crate::renderer::PancursesRenderer;
+use crate::style::Style;
 
 use iced_native::widget::text;
 use iced_native::{Color, Font, HorizontalAlignment, Rectangle, Size, VerticalAlignment};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
+/// Character ending the lines that are cut
+const ELLIPSIS: char = '…';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// How text lines that are too long for their bounds are displayed
+pub enum WrapMode {
+    /// Lines are wrapped between words. Words too long for a line are split, with a hyphen.
+    #[default]
+    Word,
+    /// Lines are wrapped at the last column, even in the middle of a word
+    Char,
+    /// Lines are not wrapped, but cut with an ellipsis
+    None,
+}
+
+/// Size from which texts are printed in bold, to stand out as headings
+pub const HEADING_SIZE: u16 = 2;
+/// Size from which texts are printed in bold and underlined, to stand out as titles
+pub const TITLE_SIZE: u16 = 3;
+
+/// Terminals have a single font of a single size: the font and the size of a text are
+/// translated into text attributes instead.
+///
+/// * `Font::External` fonts are matched by name, case insensitively:
+///   names containing "bold" are printed in bold, "italic" or "oblique" in italic,
+///   "underline" underlined, and "light" or "thin" dimmed. A "Fira Sans Bold Italic" font
+///   is printed in bold and italic.
+/// * Texts of `HEADING_SIZE` or more are printed in bold, and the ones of `TITLE_SIZE`
+///   or more are underlined too. The default size is 1.
+///
+/// Neither the font nor the size change the space taken by a text.
 impl text::Renderer for PancursesRenderer {
     fn default_size(&self) -> u16 {
         1
@@ -13,7 +47,7 @@ impl text::Renderer for PancursesRenderer {
         let content: String = content.into();
         let max_x = bounds.width as u32;
         let max_y = bounds.height as u32;
-        let layout = TextLayout::compute_layout(&content, max_x, max_y);
+        let layout = TextLayout::compute_layout(&content, max_x, max_y, self.wrap_mode);
         (layout.0 as f32, layout.1 as f32)
     }
 
@@ -21,20 +55,75 @@ impl text::Renderer for PancursesRenderer {
         &mut self,
         bounds: Rectangle,
         content: &str,
-        _size: u16,
-        _font: Font,
+        size: u16,
+        font: Font,
         color: Option<Color>,
         horizontal_alignment: HorizontalAlignment,
-        _vertical_alignment: VerticalAlignment,
+        vertical_alignment: VerticalAlignment,
     ) -> Self::Output {
         let wrapped_content = TextLayout::wrap(
             content,
             bounds.width as u32,
-            bounds.height as u32,
+            u32::MAX,
             horizontal_alignment,
+            self.wrap_mode,
         );
-        Primitive::Text(wrapped_content, bounds, color.unwrap_or(Color::WHITE))
+        let lines = TextLayout::cut_lines(wrapped_content, bounds.height as u32, self.ellipsis);
+        // The lines are moved down in the bounds when they do not fill them
+        let free_lines = (bounds.height - lines.len() as f32).max(0.);
+        let offset = match vertical_alignment {
+            VerticalAlignment::Top => 0.,
+            VerticalAlignment::Center => (free_lines / 2.).floor(),
+            VerticalAlignment::Bottom => free_lines,
+        };
+        Primitive::Text(
+            lines,
+            Rectangle {
+                y: bounds.y + offset,
+                ..bounds
+            },
+            text_style(Style::new(color.unwrap_or(Color::WHITE)), font, size),
+        )
+    }
+}
+
+/// Adds the text attributes matching the font and the size of a text to its style
+pub fn text_style(mut style: Style, font: Font, size: u16) -> Style {
+    if let Font::External { name, .. } = font {
+        let words = font_words(name);
+        let has = |keywords: &[&str]| keywords.iter().any(|k| words.iter().any(|w| w == k));
+        style.bold |= has(&["bold"]);
+        style.italic |= has(&["italic", "oblique"]);
+        style.underline |= has(&["underline"]);
+        style.dim |= has(&["light", "thin"]);
+    }
+    style.bold |= size >= HEADING_SIZE;
+    style.underline |= size >= TITLE_SIZE;
+    style
+}
+
+/// Splits the name of a font into lowercase words, separated by anything but letters and digits,
+/// or by a change of case as in "BoldItalic"
+fn font_words(name: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut previous_lowercase = false;
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            previous_lowercase = false;
+            words.push(String::new());
+            continue;
+        }
+        if c.is_uppercase() && previous_lowercase {
+            words.push(String::new());
+        }
+        previous_lowercase = c.is_lowercase();
+        match words.last_mut() {
+            Some(word) => word.extend(c.to_lowercase()),
+            None => words.push(c.to_lowercase().collect()),
+        }
     }
+    words.retain(|word| !word.is_empty());
+    words
 }
 
 pub struct TextLayout;
@@ -43,66 +132,213 @@ impl TextLayout {
     /// Computes a correct layout size. This is the minimum size that the text component has to
     /// take in order to be displayed correctly.
     /// Wraps the text if it is bigger than the bounds.
-    pub fn compute_layout(content: &str, max_x: u32, max_y: u32) -> (u32, u32) {
-        // max_len should be length of the longest line in the content
-        let mut max_len = 0;
-
-        // Computes the number of lines after they've been wrapped
-        let lines: u32 = content
-            .lines()
-            .map(|l| {
-                let chars = l.chars().count() as u32;
-                max_len = max_len.max(chars);
-                let offset = if chars % max_x == 0 { 0 } else { 1 };
-                (chars / max_x) + offset
-            })
-            .sum();
-        (max_len.min(max_x), lines.min(max_y))
+    pub fn compute_layout(content: &str, max_x: u32, max_y: u32, mode: WrapMode) -> (u32, u32) {
+        let lines = TextLayout::split(content, max_x, mode);
+        // The width is the one of the longest line, once wrapped
+        let width = lines.iter().map(|(_, width)| *width).max().unwrap_or(0);
+        (width, (lines.len() as u32).min(max_y))
     }
 
     /// Compute lines as they should be displayed on the screen, given :
     /// * The bounds of the text box (max_x, max_y)
     /// * The Horizontal Alignement of a text
-    pub fn wrap(content: &str, max_x: u32, max_y: u32, align: HorizontalAlignment) -> Vec<String> {
-        let (wrapped_x, _) = TextLayout::compute_layout(content, max_x, max_y);
-        content
-            .lines()
-            .flat_map(|l| {
-                let len = l.chars().count() as u32;
-                if len > wrapped_x {
-                    l.as_bytes()
-                        .chunks(wrapped_x as usize)
-                        .map(|bytes| String::from_utf8(bytes.to_vec()).unwrap())
-                        .collect()
-                } else {
-                    let diff = wrapped_x - len;
-                    match align {
-                        HorizontalAlignment::Left => {
-                            let padding: String = (0..diff).map(|_| ' ').collect();
-                            vec![format!("{}{}", l, padding)]
-                        }
-                        HorizontalAlignment::Center => {
-                            let pad = diff / 2;
-                            let padding: String = (0..pad).map(|_| ' ').collect();
-                            let offset = if diff % 2 == 0 { "" } else { " " };
-                            vec![format!("{}{}{}{}", offset, padding, l, padding)]
-                        }
-                        HorizontalAlignment::Right => {
-                            let padding: String = (0..diff).map(|_| ' ').collect();
-                            vec![format!("{}{}", padding, l)]
-                        }
+    /// * The way lines too long are wrapped
+    pub fn wrap(
+        content: &str,
+        max_x: u32,
+        max_y: u32,
+        align: HorizontalAlignment,
+        mode: WrapMode,
+    ) -> Vec<String> {
+        let lines = TextLayout::split(content, max_x, mode);
+        let wrapped_x = lines.iter().map(|(_, width)| *width).max().unwrap_or(0);
+        let lines: Vec<String> = lines
+            .into_iter()
+            .map(|(l, width)| {
+                let diff = wrapped_x - width;
+                match align {
+                    HorizontalAlignment::Left => {
+                        let padding: String = (0..diff).map(|_| ' ').collect();
+                        format!("{}{}", l, padding)
+                    }
+                    HorizontalAlignment::Center => {
+                        let pad = diff / 2;
+                        let padding: String = (0..pad).map(|_| ' ').collect();
+                        let offset = if diff % 2 == 0 { "" } else { " " };
+                        format!("{}{}{}{}", offset, padding, l, padding)
+                    }
+                    HorizontalAlignment::Right => {
+                        let padding: String = (0..diff).map(|_| ' ').collect();
+                        format!("{}{}", padding, l)
                     }
                 }
             })
+            .collect();
+        TextLayout::cut_lines(lines, max_y, false)
+    }
+
+    /// Drops the lines that do not fit in the given height.
+    /// With `ellipsis`, the last line kept ends with an ellipsis when lines are dropped.
+    pub fn cut_lines(mut lines: Vec<String>, max_y: u32, ellipsis: bool) -> Vec<String> {
+        if lines.len() <= max_y as usize {
+            return lines;
+        }
+        lines.truncate(max_y as usize);
+        if let (true, Some(last)) = (ellipsis, lines.last_mut()) {
+            // The ellipsis follows the text, replacing its last character if the line is full
+            let width = TextLayout::width(last);
+            let text = last.trim_end();
+            let text_width = TextLayout::width(text);
+            let mut line = if text_width < width {
+                text.to_string()
+            } else {
+                TextLayout::slice(text, 0, width.max(1) - 1)
+            };
+            line.push(ELLIPSIS);
+            let padding = width.saturating_sub(TextLayout::width(&line));
+            line.extend((0..padding).map(|_| ' '));
+            *last = line;
+        }
+        lines
+    }
+
+    /// Gets the number of columns taken by a text on the screen
+    pub fn width(content: &str) -> u32 {
+        content.width() as u32
+    }
+
+    /// Gets the part of a line displayed in the given columns.
+    /// Wide characters that are not entirely displayed are left out.
+    pub fn slice(content: &str, start: u32, columns: u32) -> String {
+        let mut column = 0;
+        content
+            .graphemes(true)
+            .filter(|grapheme| {
+                let start_column = column;
+                column += grapheme.width() as u32;
+                start_column >= start && column <= start + columns
+            })
             .collect()
     }
+
+    /// Splits every line of the text into lines fitting in the given width, along with
+    /// their width.
+    /// Lines are split between grapheme clusters, so that characters are never broken.
+    fn split(content: &str, max_x: u32, mode: WrapMode) -> Vec<(String, u32)> {
+        let max_x = max_x.max(1);
+        let mut lines = Vec::new();
+        for l in content.lines() {
+            match mode {
+                WrapMode::Word => TextLayout::split_words(l, max_x, &mut lines),
+                WrapMode::Char => TextLayout::split_chars(l, max_x, &mut lines),
+                WrapMode::None => lines.push(TextLayout::truncate(l, max_x)),
+            }
+        }
+        lines
+    }
+
+    /// Splits a line at the last column, between any two grapheme clusters
+    fn split_chars(l: &str, max_x: u32, lines: &mut Vec<(String, u32)>) {
+        let mut line = String::new();
+        let mut width = 0;
+        for grapheme in l.graphemes(true) {
+            let grapheme_width = grapheme.width() as u32;
+            if width + grapheme_width > max_x && !line.is_empty() {
+                lines.push((std::mem::take(&mut line), width));
+                width = 0;
+            }
+            line.push_str(grapheme);
+            width += grapheme_width;
+        }
+        lines.push((line, width));
+    }
+
+    /// Splits a line at the whitespace between words, the whitespace at the split being dropped.
+    /// Every whitespace character kept is displayed as a space.
+    /// Words longer than a line are split at the last column, with a hyphen.
+    fn split_words(l: &str, max_x: u32, lines: &mut Vec<(String, u32)>) {
+        let mut line = String::new();
+        let mut width = 0;
+        // Whether a word has been put on the line, even an empty one from consecutive whitespace
+        let mut started = false;
+        // Whether the line continues a wrapped one, which then does not start with whitespace
+        let mut wrapped = false;
+        for word in l.split(char::is_whitespace) {
+            if word.is_empty() && wrapped && !started {
+                continue;
+            }
+            let word_width = TextLayout::width(word);
+            let separator = if started { 1 } else { 0 };
+            if width + separator + word_width <= max_x {
+                if started {
+                    line.push(' ');
+                }
+                line.push_str(word);
+                width += separator + word_width;
+                started = true;
+                continue;
+            }
+            if started {
+                let trimmed = line.trim_end_matches(' ').len();
+                width -= (line.len() - trimmed) as u32;
+                line.truncate(trimmed);
+                if !line.is_empty() {
+                    lines.push((std::mem::take(&mut line), width));
+                }
+            }
+            wrapped = true;
+            if word.is_empty() {
+                line.clear();
+                width = 0;
+                started = false;
+                continue;
+            }
+            if word_width <= max_x {
+                line.push_str(word);
+                width = word_width;
+            } else {
+                // The hyphen needs a column of its own, next to at least one character
+                let hyphen = max_x > 1;
+                let mut pieces = Vec::new();
+                TextLayout::split_chars(word, max_x - hyphen as u32, &mut pieces);
+                let (last, last_width) = pieces.pop().unwrap_or_default();
+                for (piece, piece_width) in pieces {
+                    if hyphen {
+                        lines.push((format!("{}-", piece), piece_width + 1));
+                    } else {
+                        lines.push((piece, piece_width));
+                    }
+                }
+                line = last;
+                width = last_width;
+            }
+            started = true;
+        }
+        lines.push((line, width));
+    }
+
+    /// Cuts a line at the last column, ending it with an ellipsis if it is too long
+    fn truncate(l: &str, max_x: u32) -> (String, u32) {
+        let width = TextLayout::width(l);
+        if width <= max_x {
+            return (l.into(), width);
+        }
+        let mut line = TextLayout::slice(l, 0, max_x - 1);
+        let width = TextLayout::width(&line) + 1;
+        line.push(ELLIPSIS);
+        (line, width)
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
 
-    use super::TextLayout;
-    use iced_native::HorizontalAlignment;
+    use super::{text_style, TextLayout, WrapMode};
+    use crate::primitive::Primitive;
+    use crate::style::Style;
+    use crate::PancursesRenderer;
+    use iced_native::widget::text;
+    use iced_native::{Font, HorizontalAlignment, Rectangle, VerticalAlignment};
 
     #[test]
     pub fn text_layout_compute_should_work() {
@@ -113,8 +349,14 @@ pub mod tests {
         // test!
         //
         // This means that the size it should take on a (10, 2) or bigger is always (10, 2)
-        assert_eq!(TextLayout::compute_layout(content, 10, 2), (10, 2));
-        assert_eq!(TextLayout::compute_layout(content, 15, 3), (10, 2));
+        assert_eq!(
+            TextLayout::compute_layout(content, 10, 2, WrapMode::Word),
+            (10, 2)
+        );
+        assert_eq!(
+            TextLayout::compute_layout(content, 15, 3, WrapMode::Word),
+            (10, 2)
+        );
     }
 
     #[test]
@@ -127,16 +369,80 @@ pub mod tests {
         // First
         // line
         // test!
-        assert_eq!(TextLayout::compute_layout(content, 5, 10), (5, 3));
+        assert_eq!(
+            TextLayout::compute_layout(content, 5, 10, WrapMode::Word),
+            (5, 3)
+        );
 
-        // On a (4, 10) box, the text should wrap as follows:
+        // On a (4, 10) box, words that are too long are split with a hyphen:
+        //
+        // Fir-
+        // st
+        // line
+        // tes-
+        // t!
+        assert_eq!(
+            TextLayout::wrap(content, 4, 10, HorizontalAlignment::Left, WrapMode::Word),
+            vec!["Fir-", "st  ", "line", "tes-", "t!  "]
+        );
+
+        // When wrapping at any character, the text should wrap as follows:
         //
         // Firs
         // t li
         // ne
         // test
         // !
-        assert_eq!(TextLayout::compute_layout(content, 4, 10), (4, 5));
+        assert_eq!(
+            TextLayout::compute_layout(content, 4, 10, WrapMode::Char),
+            (4, 5)
+        );
+
+        // And without wrapping, lines are cut:
+        //
+        // Fir…
+        // tes…
+        assert_eq!(
+            TextLayout::wrap(content, 4, 10, HorizontalAlignment::Left, WrapMode::None),
+            vec!["Fir…", "tes…"]
+        );
+    }
+
+    #[test]
+    pub fn text_layout_should_wrap_at_any_whitespace() {
+        // Tabs and other whitespace separate words, and are displayed as spaces
+        assert_eq!(
+            TextLayout::wrap(
+                "a\tb\u{3000}c",
+                3,
+                10,
+                HorizontalAlignment::Left,
+                WrapMode::Word
+            ),
+            vec!["a b", "c  "]
+        );
+
+        // Wrapped lines neither end nor start with the whitespace at the break
+        assert_eq!(
+            TextLayout::wrap(
+                "ab    cd ef",
+                3,
+                10,
+                HorizontalAlignment::Left,
+                WrapMode::Word
+            ),
+            vec!["ab", "cd", "ef"]
+        );
+        assert_eq!(
+            TextLayout::compute_layout("ab cd", 4, 10, WrapMode::Word),
+            (2, 2)
+        );
+
+        // The indentation of a line is kept
+        assert_eq!(
+            TextLayout::wrap("  ab cd", 5, 10, HorizontalAlignment::Left, WrapMode::Word),
+            vec!["  ab", "cd  "]
+        );
     }
 
     #[test]
@@ -145,20 +451,128 @@ pub mod tests {
 
         // Lets try normal layoung with Left alignment
         assert_eq!(
-            TextLayout::wrap(content, 10, 2, HorizontalAlignment::Left),
+            TextLayout::wrap(content, 10, 2, HorizontalAlignment::Left, WrapMode::Word),
             vec!["First line", "test!     "]
         );
 
         // ... Center ...
         assert_eq!(
-            TextLayout::wrap(content, 10, 2, HorizontalAlignment::Center),
+            TextLayout::wrap(content, 10, 2, HorizontalAlignment::Center, WrapMode::Word),
             vec!["First line", "   test!  "]
         );
 
         // ... and Right
         assert_eq!(
-            TextLayout::wrap(content, 10, 2, HorizontalAlignment::Right),
+            TextLayout::wrap(content, 10, 2, HorizontalAlignment::Right, WrapMode::Word),
             vec!["First line", "     test!"]
         )
     }
+
+    #[test]
+    pub fn text_layout_should_handle_unicode() {
+        // Ideographs take two columns each, and are never split
+        let content = "日本語";
+        assert_eq!(
+            TextLayout::compute_layout(content, 10, 10, WrapMode::Word),
+            (6, 1)
+        );
+        assert_eq!(
+            TextLayout::wrap(content, 5, 10, HorizontalAlignment::Left, WrapMode::Char),
+            vec!["日本", "語  "]
+        );
+
+        // Combining marks stay with the character they modify
+        let content = "cafe\u{301}s";
+        assert_eq!(
+            TextLayout::compute_layout(content, 10, 10, WrapMode::Word),
+            (5, 1)
+        );
+        assert_eq!(
+            TextLayout::wrap(content, 4, 10, HorizontalAlignment::Left, WrapMode::Char),
+            vec!["cafe\u{301}", "s   "]
+        );
+        assert_eq!(TextLayout::slice("a日本", 2, 3), "本");
+    }
+
+    #[test]
+    pub fn text_layout_should_cut_lines() {
+        let lines = vec!["First".into(), "line ".into(), "test!".into()];
+        assert_eq!(
+            TextLayout::cut_lines(lines.clone(), 2, false),
+            vec!["First", "line "]
+        );
+        // The ellipsis follows the text, or replaces its last character
+        assert_eq!(
+            TextLayout::cut_lines(lines.clone(), 2, true),
+            vec!["First", "line…"]
+        );
+        assert_eq!(TextLayout::cut_lines(lines, 1, true), vec!["Firs…"]);
+    }
+
+    #[test]
+    pub fn text_should_be_aligned_vertically() {
+        let mut renderer = PancursesRenderer::headless(5, 4);
+        let bounds = Rectangle {
+            x: 0.,
+            y: 0.,
+            width: 5.,
+            height: 4.,
+        };
+        let primitive = text::Renderer::draw(
+            &mut renderer,
+            bounds,
+            "Hi",
+            1,
+            Font::Default,
+            None,
+            HorizontalAlignment::Center,
+            VerticalAlignment::Bottom,
+        );
+        match &primitive {
+            Primitive::Text(lines, bounds, _) => {
+                assert_eq!(lines, &vec!["Hi".to_string()]);
+                assert_eq!(bounds.y, 3.);
+            }
+            _ => panic!("Text should be drawn as a text primitive"),
+        }
+    }
+
+    #[test]
+    pub fn text_style_should_match_font_and_size() {
+        let style = Style::default();
+        assert_eq!(text_style(style, Font::Default, 1), style);
+        assert_eq!(text_style(style, Font::Default, 2), style.bold());
+        assert_eq!(
+            text_style(style, Font::Default, 3),
+            style.bold().underline()
+        );
+
+        let font = Font::External {
+            name: "Fira Sans Bold Italic",
+            bytes: &[],
+        };
+        assert_eq!(text_style(style, font, 1), style.bold().italic());
+        let font = Font::External {
+            name: "Roboto-Light",
+            bytes: &[],
+        };
+        assert_eq!(text_style(style, font, 1), style.dim());
+        let font = Font::External {
+            name: "OpenSans-SemiBoldItalic",
+            bytes: &[],
+        };
+        assert_eq!(text_style(style, font, 1), style.bold().italic());
+
+        // Only whole words are keywords
+        let font = Font::External {
+            name: "Highlight Boldly",
+            bytes: &[],
+        };
+        assert_eq!(text_style(style, font, 1), style);
+        let font = Font::External {
+            name: "Nothing You Could Do",
+            bytes: &[],
+        };
+        assert_eq!(text_style(style, font, 1), style);
+    }
 }
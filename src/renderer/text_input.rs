@@ -1,43 +1,171 @@
+use crate::focus::FocusKind;
 use crate::primitive::Primitive;
+use crate::renderer::text::TextLayout;
+use crate::style::Style;
 use crate::PancursesRenderer;
 use iced_native::widget::text_input;
-use iced_native::{Font, HorizontalAlignment, Point, Rectangle, VerticalAlignment};
+use iced_native::{Point, Rectangle};
 
 impl text_input::Renderer for PancursesRenderer {
     fn default_size(&self) -> u16 {
         3
     }
 
+    fn measure_value(&self, value: &str, _size: u16) -> f32 {
+        TextLayout::width(value) as f32
+    }
+
+    fn offset(
+        &self,
+        text_bounds: Rectangle,
+        size: u16,
+        value: &text_input::Value,
+        state: &text_input::State,
+    ) -> f32 {
+        if !state.is_focused() {
+            return 0.;
+        }
+        let area = text_area(text_bounds);
+        let cursor = state.cursor_position(value);
+        let until_cursor = self.measure_value(&value.until(cursor).to_string(), size);
+        // Scrolls just enough to keep the cursor visible, in the last column of the area
+        (until_cursor - area.width + 1.).max(0.)
+    }
+
     fn draw(
         &mut self,
         bounds: Rectangle,
-        src_text_bounds: Rectangle,
+        text_bounds: Rectangle,
         _cursor_position: Point,
-        _size: u16,
+        size: u16,
         placeholder: &str,
         value: &text_input::Value,
-        _state: &text_input::State,
+        state: &text_input::State,
     ) -> Primitive {
-        let mut text = value.to_string();
-        if text == "" {
-            text = placeholder.into();
+        self.focus.register(bounds, FocusKind::TextInput);
+        let area = text_area(text_bounds);
+        let visible_width = area.width as u32;
+        let offset = self.offset(text_bounds, size, value, state) as u32;
+
+        // Iced masks the value of password inputs with `Value::secure` before drawing them,
+        // so it can be displayed as is
+        let text = value.to_string();
+        // The placeholder is dimmed, to tell it apart from an actual value
+        let (line, style) = if text.is_empty() {
+            (
+                TextLayout::slice(placeholder, 0, visible_width),
+                Style::default().dim(),
+            )
+        } else {
+            (
+                TextLayout::slice(&text, offset, visible_width),
+                Style::default(),
+            )
+        };
+        let mut primitives = vec![
+            Primitive::BoxDisplay(bounds, Style::default()),
+            Primitive::Text(vec![line], area, style),
+        ];
+
+        if state.is_focused() {
+            let cursor = state.cursor_position(value);
+            let until_cursor = self.measure_value(&value.until(cursor).to_string(), size);
+            primitives.push(Primitive::Cursor(
+                area.x as i32 + until_cursor as i32 - offset as i32,
+                area.y as i32,
+            ));
         }
-        let bounds_text = Rectangle {
-            width: src_text_bounds.width,
-            height: src_text_bounds.height,
-            x: src_text_bounds.x + 1.,
-            y: src_text_bounds.y + 1.,
+        Primitive::Group(primitives)
+    }
+}
+
+/// Gets the area of the input in which the value is displayed: the line inside the border
+fn text_area(text_bounds: Rectangle) -> Rectangle {
+    Rectangle {
+        x: text_bounds.x + 1.,
+        y: text_bounds.y + 1.,
+        width: (text_bounds.width - 2.).max(1.),
+        height: 1.,
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use crate::PancursesRenderer;
+    use iced_native::widget::text_input::{self, State, Value};
+    use iced_native::{Point, Rectangle};
+
+    /// Draws an input 7 columns wide, showing 5 columns of its value
+    fn draw_input(placeholder: &str, value: &Value, state: &State) -> PancursesRenderer {
+        let mut renderer = PancursesRenderer::headless(7, 3);
+        let bounds = Rectangle {
+            x: 0.,
+            y: 0.,
+            width: 7.,
+            height: 3.,
+        };
+        let primitive = text_input::Renderer::draw(
+            &mut renderer,
+            bounds,
+            bounds,
+            Point::new(-1., -1.),
+            3,
+            placeholder,
+            value,
+            state,
+        );
+        renderer.draw(primitive);
+        renderer
+    }
+
+    #[test]
+    pub fn text_input_should_draw_dimmed_placeholder() {
+        let renderer = draw_input("Name", &Value::new(""), &State::new());
+        let backend = renderer.headless_backend().unwrap();
+        assert_eq!(backend.line(1), "│Name │");
+        assert!(backend.cell(1, 1).unwrap().style.dim);
+        assert_eq!(backend.cursor(), None);
+    }
+
+    #[test]
+    pub fn text_input_should_place_cursor_after_value() {
+        let renderer = draw_input("Name", &Value::new("abc"), &State::focused());
+        let backend = renderer.headless_backend().unwrap();
+        assert_eq!(backend.line(1), "│abc  │");
+        assert!(!backend.cell(1, 1).unwrap().style.dim);
+        assert_eq!(backend.cursor(), Some((4, 1)));
+    }
+
+    #[test]
+    pub fn text_input_should_scroll_long_value() {
+        let value = Value::new("abcdefgh");
+        let state = State::focused();
+        let renderer = draw_input("", &value, &state);
+        let bounds = Rectangle {
+            x: 0.,
+            y: 0.,
+            width: 7.,
+            height: 3.,
         };
-        let prim_text = <Self as iced_native::widget::text::Renderer>::draw(
-            self,
-            bounds_text,
-            &text,
-            1,
-            Font::Default,
-            None,
-            HorizontalAlignment::Left,
-            VerticalAlignment::Top,
+        // The value is scrolled to keep the cursor in the last column
+        assert_eq!(
+            text_input::Renderer::offset(&renderer, bounds, 3, &value, &state),
+            4.
         );
-        Primitive::Group(vec![Primitive::BoxDisplay(bounds), prim_text])
+        let backend = renderer.headless_backend().unwrap();
+        assert_eq!(backend.line(1), "│efgh │");
+        assert_eq!(backend.cursor(), Some((5, 1)));
+
+        // Unfocused inputs show the start of their value
+        let renderer = draw_input("", &value, &State::new());
+        assert_eq!(renderer.headless_backend().unwrap().line(1), "│abcde│");
+    }
+
+    #[test]
+    pub fn text_input_should_draw_masked_password() {
+        let value = Value::new("secret");
+        let renderer = draw_input("", &value.secure(), &State::new());
+        assert_eq!(renderer.headless_backend().unwrap().line(1), "│•••••│");
     }
 }
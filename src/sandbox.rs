@@ -35,7 +35,6 @@ pub trait Sandbox: Sized {
         let mut cache = Some(Cache::default());
 
         loop {
-            renderer.flush();
             let size = renderer.size();
             // Consumes the cache and renders the UI to primitives
             let view: Element<'_, Self::Message, PancursesRenderer> = Container::new(state.view())
@@ -44,7 +43,8 @@ pub trait Sandbox: Sized {
                 .into();
             let mut ui = UserInterface::build(view, cache.take().unwrap(), &mut renderer);
 
-            // Displays the new state of the sandbox using the renderer
+            // Displays the new state of the sandbox using the renderer.
+            // Only the parts of the screen that changed are printed again
             let primitives = ui.draw(&mut renderer);
             renderer.draw(primitives);
 
@@ -0,0 +1,90 @@
+//! Helpers to render elements off-screen, in order to write snapshot tests of views.
+//!
+//! The element goes through the same steps as in an `Application`: it is laid out, drawn to
+//! primitives by iced and finally printed by the `PancursesRenderer`, on a `HeadlessBackend`.
+
+use crate::PancursesRenderer;
+use iced_native::{Cache, Container, Element, Length, UserInterface};
+
+/// Renders the element on a screen of the given size (columns, lines),
+/// and returns the renderer holding the resulting screen
+pub fn render<'a, Message>(
+    element: Element<'a, Message, PancursesRenderer>,
+    size: (u16, u16),
+) -> PancursesRenderer {
+    let mut renderer = PancursesRenderer::headless(size.0, size.1);
+    let view: Element<'_, Message, PancursesRenderer> = Container::new(element)
+        .width(Length::Units(size.0))
+        .height(Length::Units(size.1))
+        .into();
+    let ui = UserInterface::build(view, Cache::default(), &mut renderer);
+    let primitives = ui.draw(&mut renderer);
+    renderer.draw(primitives);
+    renderer
+}
+
+/// Renders the element on a screen of the given size (columns, lines),
+/// and returns the screen as plain text
+pub fn render_to_string<'a, Message>(
+    element: Element<'a, Message, PancursesRenderer>,
+    size: (u16, u16),
+) -> String {
+    render(element, size)
+        .headless_backend()
+        .map(|backend| backend.text())
+        .unwrap_or_default()
+}
+
+/// Renders the element on a screen of the given size (columns, lines),
+/// and returns the screen as text colored with ANSI escape sequences
+pub fn render_to_ansi<'a, Message>(
+    element: Element<'a, Message, PancursesRenderer>,
+    size: (u16, u16),
+) -> String {
+    render(element, size)
+        .headless_backend()
+        .map(|backend| backend.ansi())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::{render, render_to_ansi, render_to_string};
+    use crate::PancursesRenderer;
+    use iced_native::{Color, Column, Element, Text};
+
+    fn greeting<'a>() -> Element<'a, (), PancursesRenderer> {
+        Column::new()
+            .push(Text::new("Hello"))
+            .push(Text::new("world").color(Color::from_rgb(1., 0., 0.)))
+            .into()
+    }
+
+    #[test]
+    pub fn render_should_draw_element() {
+        let renderer = render(greeting(), (6, 3));
+        let backend = renderer.headless_backend().unwrap();
+        assert_eq!(backend.lines(), vec!["Hello ", "world ", "      "]);
+        let cell = backend.cell(0, 1).unwrap();
+        assert_eq!(cell.style.foreground, Color::from_rgb(1., 0., 0.));
+    }
+
+    #[test]
+    pub fn render_to_string_should_return_screen() {
+        assert_eq!(
+            render_to_string(greeting(), (6, 3)),
+            "Hello \nworld \n      "
+        );
+    }
+
+    #[test]
+    pub fn render_to_ansi_should_return_colored_screen() {
+        let element: Element<'_, (), PancursesRenderer> =
+            Text::new("Hi").color(Color::from_rgb(1., 0., 0.)).into();
+        assert_eq!(
+            render_to_ansi(element, (3, 1)),
+            "\x1b[0;38;2;255;0;0;49mHi\x1b[0;38;2;255;255;255;49m \x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use iced_native::Color;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The visual style of a terminal cell
+pub struct Style {
+    /// Color of the character printed in the cell
+    pub foreground: Color,
+    /// Color of the cell itself. With `None`, the background already drawn in the cell is kept,
+    /// the terminal default background being used when there is none.
+    pub background: Option<Color>,
+    /// Whether the character is printed in bold
+    pub bold: bool,
+    /// Whether the character is underlined
+    pub underline: bool,
+    /// Whether the foreground and background colors are swapped
+    pub reverse: bool,
+    /// Whether the character is printed with a lower intensity
+    pub dim: bool,
+    /// Whether the character is printed in italic, on terminals supporting it
+    pub italic: bool,
+    /// Whether the character blinks
+    pub blink: bool,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            foreground: Color::WHITE,
+            background: None,
+            bold: false,
+            underline: false,
+            reverse: false,
+            dim: false,
+            italic: false,
+            blink: false,
+        }
+    }
+}
+
+impl Style {
+    /// Creates a style printing characters with the given color on the default background
+    pub fn new(foreground: Color) -> Self {
+        Style {
+            foreground,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the color of the character printed in the cell
+    pub fn foreground(mut self, color: Color) -> Self {
+        self.foreground = color;
+        self
+    }
+
+    /// Sets the color of the cell itself
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Prints the character in bold
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    /// Underlines the character
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    /// Swaps the foreground and background colors
+    pub fn reverse(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+
+    /// Prints the character with a lower intensity
+    pub fn dim(mut self) -> Self {
+        self.dim = true;
+        self
+    }
+
+    /// Prints the character in italic
+    pub fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    /// Makes the character blink
+    pub fn blink(mut self) -> Self {
+        self.blink = true;
+        self
+    }
+}
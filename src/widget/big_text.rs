@@ -0,0 +1,170 @@
+use crate::figlet::FigletFont;
+use crate::primitive::Primitive;
+use crate::style::Style;
+use crate::PancursesRenderer;
+
+use iced_native::{layout, Color, Element, Hasher, Layout, Length, Point, Size, Widget};
+use std::hash::Hash;
+use std::rc::Rc;
+use unicode_width::UnicodeWidthStr;
+
+thread_local! {
+    /// The bundled font, parsed once
+    static BLOCKS_FONT: Rc<FigletFont> = Rc::new(FigletFont::blocks());
+}
+
+/// A text drawn in large ASCII art characters, using a FIGlet font.
+///
+/// Useful for headings and dashboards that have to be readable from afar.
+/// The bundled `FigletFont::blocks` font is used by default: fonts loaded from `.flf` files
+/// can be used instead.
+///
+/// ```no_run
+/// # use iced_pancurses::{BigText, FigletFont};
+/// # use std::rc::Rc;
+/// let font = Rc::new(FigletFont::from_file("fonts/standard.flf").unwrap());
+/// let title = BigText::new("12:34").font(font);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BigText {
+    content: String,
+    font: Rc<FigletFont>,
+    color: Option<Color>,
+}
+
+impl BigText {
+    /// Creates a big text, drawn with the bundled font
+    pub fn new<T: Into<String>>(content: T) -> Self {
+        BigText {
+            content: content.into(),
+            font: BLOCKS_FONT.with(Rc::clone),
+            color: None,
+        }
+    }
+
+    /// Sets the font used to draw the text
+    pub fn font(mut self, font: Rc<FigletFont>) -> Self {
+        self.font = font;
+        self
+    }
+
+    /// Sets the color of the text
+    pub fn color<C: Into<Color>>(mut self, color: C) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    /// Draws the content with the font
+    fn lines(&self) -> Vec<String> {
+        self.font.render(&self.content)
+    }
+}
+
+impl<Message> Widget<Message, PancursesRenderer> for BigText {
+    fn width(&self) -> Length {
+        Length::Shrink
+    }
+
+    fn height(&self) -> Length {
+        Length::Shrink
+    }
+
+    fn layout(&self, _renderer: &PancursesRenderer, limits: &layout::Limits) -> layout::Node {
+        let lines = self.lines();
+        let width = lines.iter().map(|line| line.width()).max().unwrap_or(0);
+        let limits = limits.width(Length::Shrink).height(Length::Shrink);
+        let size = limits.resolve(Size::new(width as f32, lines.len() as f32));
+        layout::Node::new(size)
+    }
+
+    fn draw(
+        &self,
+        _renderer: &mut PancursesRenderer,
+        layout: Layout<'_>,
+        _cursor_position: Point,
+    ) -> Primitive {
+        let bounds = layout.bounds();
+        // The lines are cut when the layout does not leave room for them
+        Primitive::Clip(
+            bounds,
+            Box::new(Primitive::Text(
+                self.lines(),
+                bounds,
+                Style::new(self.color.unwrap_or(Color::WHITE)),
+            )),
+        )
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        std::any::TypeId::of::<BigText>().hash(state);
+        self.content.hash(state);
+        // Fonts are shared: the same font is at the same address
+        (Rc::as_ptr(&self.font) as usize).hash(state);
+    }
+}
+
+impl<'a, Message> From<BigText> for Element<'a, Message, PancursesRenderer> {
+    fn from(big_text: BigText) -> Element<'a, Message, PancursesRenderer> {
+        Element::new(big_text)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::BigText;
+    use crate::figlet::FigletFont;
+    use crate::PancursesRenderer;
+    use iced_native::{layout, Layout, Point, Size, Widget};
+    use std::rc::Rc;
+
+    /// Creates a font of 2 lines tall glyphs, drawing A with the given lines
+    fn font(a: &str) -> Rc<FigletFont> {
+        let mut font = String::from("flf2a$ 2 2 8 -1 0\n");
+        for c in 32..127 {
+            match c as u8 {
+                b'A' => font.push_str(a),
+                _ => font.push_str("@\n@@\n"),
+            }
+        }
+        Rc::new(FigletFont::parse(&font).unwrap())
+    }
+
+    #[test]
+    pub fn big_text_should_measure_display_width() {
+        // Ideographs take two columns each
+        let mut font = String::from("flf2a$ 1 1 4 -1 0\n");
+        for c in 32..127 {
+            match c as u8 {
+                b'A' => font.push_str("日本@@\n"),
+                _ => font.push_str("@@\n"),
+            }
+        }
+        let font = Rc::new(FigletFont::parse(&font).unwrap());
+        let big_text = BigText::new("A").font(font);
+        let renderer = PancursesRenderer::headless(10, 1);
+        let node =
+            Widget::<(), PancursesRenderer>::layout(&big_text, &renderer, &layout::Limits::NONE);
+        assert_eq!(node.size(), Size::new(4., 1.));
+    }
+
+    #[test]
+    pub fn big_text_should_be_cut_to_its_bounds() {
+        let big_text = BigText::new("A").font(font("abcd@\nefgh@@\n"));
+        let mut renderer = PancursesRenderer::headless(5, 2);
+        // The text is only given 3 columns and a line
+        let limits = layout::Limits::new(Size::ZERO, Size::new(3., 1.));
+        let node = Widget::<(), PancursesRenderer>::layout(&big_text, &renderer, &limits);
+        let primitive = Widget::<(), PancursesRenderer>::draw(
+            &big_text,
+            &mut renderer,
+            Layout::new(&node),
+            Point::ORIGIN,
+        );
+        renderer.draw(primitive);
+        assert_eq!(
+            renderer.headless_backend().unwrap().lines(),
+            vec!["abc  ", "     "]
+        );
+    }
+}
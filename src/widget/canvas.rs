@@ -0,0 +1,496 @@
+use crate::primitive::Primitive;
+use crate::style::Style;
+use crate::PancursesRenderer;
+
+use iced_native::{layout, Color, Element, Hasher, Layout, Length, Point, Rectangle, Size, Widget};
+use std::hash::Hash;
+
+/// First Braille character, with no dot raised
+const BRAILLE_BLANK: u32 = 0x2800;
+/// Bit of each dot of a Braille character, by line and column of the dot
+const BRAILLE_DOTS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+/// Maximum number of segments drawing a circle, which keeps huge circles quick to draw
+const MAX_CIRCLE_SEGMENTS: usize = 4096;
+
+/// A shape drawn on a canvas, in the coordinates of the canvas
+#[derive(Debug, Clone)]
+enum Shape {
+    Points(Vec<(f32, f32)>, Color),
+    Polyline(Vec<(f32, f32)>, Color),
+    Circle(f32, f32, f32, Color),
+    Label(f32, f32, String, Color),
+}
+
+/// An area to draw lines, shapes and plots in, with Braille characters.
+///
+/// Each cell holds 2x4 dots, which gives 8 times the resolution of characters.
+/// Shapes are placed in the coordinates of the canvas, defined by its bounds: `x` goes from left
+/// to right and `y` from bottom to top, both from 0 to 1 by default.
+///
+/// A cell only has one color: where shapes of different colors meet, the last drawn wins.
+/// Labels are drawn as text, over the shapes.
+///
+/// ```no_run
+/// # use iced_pancurses::Canvas;
+/// # use iced_native::Color;
+/// let samples = vec![(0., 2.), (1., 5.), (2., 3.), (3., 8.)];
+/// let chart = Canvas::new()
+///     .x_bounds(0., 3.)
+///     .y_bounds(0., 10.)
+///     .polyline(samples, Color::WHITE)
+///     .label(0., 10., "Load", Color::WHITE);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    width: Length,
+    height: Length,
+    x_bounds: (f32, f32),
+    y_bounds: (f32, f32),
+    shapes: Vec<Shape>,
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Canvas {
+            width: Length::Fill,
+            height: Length::Fill,
+            x_bounds: (0., 1.),
+            y_bounds: (0., 1.),
+            shapes: Vec::new(),
+        }
+    }
+}
+
+impl Canvas {
+    /// Creates an empty canvas, filling the available space
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the width of the canvas
+    pub fn width(mut self, width: Length) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets the height of the canvas
+    pub fn height(mut self, height: Length) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Sets the coordinates of the left and right sides of the canvas.
+    /// Nothing is drawn when they are equal.
+    pub fn x_bounds(mut self, left: f32, right: f32) -> Self {
+        self.x_bounds = (left, right);
+        self
+    }
+
+    /// Sets the coordinates of the bottom and top sides of the canvas.
+    /// Nothing is drawn when they are equal.
+    pub fn y_bounds(mut self, bottom: f32, top: f32) -> Self {
+        self.y_bounds = (bottom, top);
+        self
+    }
+
+    /// Draws a single dot
+    pub fn point(self, x: f32, y: f32, color: Color) -> Self {
+        self.points(vec![(x, y)], color)
+    }
+
+    /// Draws a dot at each of the given points, as in a scatter plot
+    pub fn points<I: IntoIterator<Item = (f32, f32)>>(mut self, points: I, color: Color) -> Self {
+        self.shapes
+            .push(Shape::Points(points.into_iter().collect(), color));
+        self
+    }
+
+    /// Draws a line between two points
+    pub fn line(self, x1: f32, y1: f32, x2: f32, y2: f32, color: Color) -> Self {
+        self.polyline(vec![(x1, y1), (x2, y2)], color)
+    }
+
+    /// Draws lines joining the given points one after the other, as in a line chart
+    pub fn polyline<I: IntoIterator<Item = (f32, f32)>>(mut self, points: I, color: Color) -> Self {
+        self.shapes
+            .push(Shape::Polyline(points.into_iter().collect(), color));
+        self
+    }
+
+    /// Draws the outline of a rectangle, from its bottom left corner and its size
+    pub fn rectangle(self, x: f32, y: f32, width: f32, height: f32, color: Color) -> Self {
+        let corners = vec![
+            (x, y),
+            (x + width, y),
+            (x + width, y + height),
+            (x, y + height),
+            (x, y),
+        ];
+        self.polyline(corners, color)
+    }
+
+    /// Draws the outline of a circle, from its center and its radius.
+    /// The circle is stretched like the canvas when its axes have different scales.
+    pub fn circle(mut self, x: f32, y: f32, radius: f32, color: Color) -> Self {
+        self.shapes.push(Shape::Circle(x, y, radius, color));
+        self
+    }
+
+    /// Writes a text, starting from the cell of the given point
+    pub fn label<T: Into<String>>(mut self, x: f32, y: f32, text: T, color: Color) -> Self {
+        self.shapes.push(Shape::Label(x, y, text.into(), color));
+        self
+    }
+
+    /// Draws the shapes of the canvas in the given bounds
+    fn primitives(&self, bounds: Rectangle) -> Primitive {
+        let mut grid = BrailleGrid::new(bounds.width as usize, bounds.height as usize);
+        let (dots_width, dots_height) = grid.dots();
+        let (left, right) = self.x_bounds;
+        let (bottom, top) = self.y_bounds;
+        let is_valid = |span: f32| span.is_finite() && span != 0.;
+        if grid.cells.is_empty() || !is_valid(right - left) || !is_valid(top - bottom) {
+            return Primitive::Empty;
+        }
+        // Coordinates are mapped to the center of the dots
+        let to_dots = |(x, y): (f32, f32)| {
+            (
+                (x - left) / (right - left) * (dots_width as f32 - 1.),
+                (top - y) / (top - bottom) * (dots_height as f32 - 1.),
+            )
+        };
+        let mut labels = Vec::new();
+        for shape in &self.shapes {
+            match shape {
+                Shape::Points(points, color) => points
+                    .iter()
+                    .for_each(|point| grid.set(to_dots(*point), *color)),
+                Shape::Polyline(points, color) => points
+                    .windows(2)
+                    .for_each(|line| grid.line(to_dots(line[0]), to_dots(line[1]), *color)),
+                Shape::Circle(x, y, radius, color) => {
+                    let center = to_dots((*x, *y));
+                    let (radius_x, _) = to_dots((left + radius, bottom));
+                    let (_, radius_y) = to_dots((left, top - radius));
+                    // Enough segments for them to be shorter than a dot, unless the circle is huge
+                    let perimeter = std::f32::consts::TAU * radius_x.abs().max(radius_y.abs());
+                    let segments = (perimeter.ceil() as usize).clamp(8, MAX_CIRCLE_SEGMENTS);
+                    let dot = |idx: usize| {
+                        let angle = std::f32::consts::TAU * idx as f32 / segments as f32;
+                        (
+                            center.0 + radius_x * angle.cos(),
+                            center.1 + radius_y * angle.sin(),
+                        )
+                    };
+                    (0..segments).for_each(|idx| grid.line(dot(idx), dot(idx + 1), *color));
+                }
+                Shape::Label(x, y, text, color) => {
+                    let (x, y) = to_dots((*x, *y));
+                    if !x.is_finite() || !y.is_finite() {
+                        continue;
+                    }
+                    labels.push(Primitive::Text(
+                        vec![text.clone()],
+                        Rectangle {
+                            x: bounds.x + (x.round() / 2.).floor(),
+                            y: bounds.y + (y.round() / 4.).floor(),
+                            width: bounds.width,
+                            height: 1.,
+                        },
+                        Style::new(*color),
+                    ));
+                }
+            }
+        }
+        let mut primitives = grid.primitives(bounds.x as i32, bounds.y as i32);
+        primitives.append(&mut labels);
+        // Labels may overflow the canvas on its right
+        Primitive::Clip(bounds, Box::new(Primitive::Group(primitives)))
+    }
+}
+
+/// Cells of Braille characters, in which dots are raised
+#[derive(Debug)]
+struct BrailleGrid {
+    width: usize,
+    height: usize,
+    /// Raised dots of each cell
+    cells: Vec<u8>,
+    /// Color of each cell
+    colors: Vec<Color>,
+}
+
+impl BrailleGrid {
+    /// Creates a grid of the given size in cells, with no dot raised
+    fn new(width: usize, height: usize) -> Self {
+        BrailleGrid {
+            width,
+            height,
+            cells: vec![0; width * height],
+            colors: vec![Color::WHITE; width * height],
+        }
+    }
+
+    /// Gets the size of the grid in dots
+    fn dots(&self) -> (usize, usize) {
+        (self.width * 2, self.height * 4)
+    }
+
+    /// Raises the dot closest to the given position, if it is on the grid
+    fn set(&mut self, (x, y): (f32, f32), color: Color) {
+        let (x, y) = (x.round(), y.round());
+        let (dots_width, dots_height) = self.dots();
+        let on_grid = x >= 0. && y >= 0. && x < dots_width as f32 && y < dots_height as f32;
+        // Invalid positions are not on the grid either
+        if !on_grid {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        let idx = y / 4 * self.width + x / 2;
+        self.cells[idx] |= BRAILLE_DOTS[y % 4][x % 2];
+        self.colors[idx] = color;
+    }
+
+    /// Raises the dots on the line between two positions.
+    /// Only the part of the line on the grid is drawn.
+    fn line(&mut self, from: (f32, f32), to: (f32, f32), color: Color) {
+        let (from, to) = match self.clip(from, to) {
+            Some(line) => line,
+            None => return,
+        };
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let steps = dx.abs().max(dy.abs()).round().max(1.);
+        for step in 0..=steps as usize {
+            let ratio = step as f32 / steps;
+            self.set((from.0 + dx * ratio, from.1 + dy * ratio), color);
+        }
+    }
+
+    /// Gets the part of the line between two positions that is on the grid, if any,
+    /// using the Cohen-Sutherland algorithm.
+    /// Invalid positions, such as infinite ones, have no part on the grid.
+    fn clip(&self, from: (f32, f32), to: (f32, f32)) -> Option<((f32, f32), (f32, f32))> {
+        if ![from.0, from.1, to.0, to.1]
+            .iter()
+            .all(|value| value.is_finite())
+        {
+            return None;
+        }
+        let (dots_width, dots_height) = self.dots();
+        if dots_width == 0 || dots_height == 0 {
+            return None;
+        }
+        // Positions are rounded to the closest dot: the grid extends by half a dot around them
+        let (min_x, min_y) = (-0.5, -0.5);
+        let (max_x, max_y) = (dots_width as f32 - 0.5, dots_height as f32 - 0.5);
+        // Sides of the grid a position is beyond, as bits
+        let outside = |(x, y): (f32, f32)| {
+            (x < min_x) as u8
+                | ((x > max_x) as u8) << 1
+                | ((y < min_y) as u8) << 2
+                | ((y > max_y) as u8) << 3
+        };
+        let (mut from, mut to) = (from, to);
+        // Each end is moved at most once per side
+        for _ in 0..8 {
+            let (from_sides, to_sides) = (outside(from), outside(to));
+            if from_sides | to_sides == 0 {
+                // Both ends are moved on the dots they are rounded to, so that none is lost
+                let to_dot = |(x, y): (f32, f32)| {
+                    (
+                        x.max(0.).min(dots_width as f32 - 1.),
+                        y.max(0.).min(dots_height as f32 - 1.),
+                    )
+                };
+                return Some((to_dot(from), to_dot(to)));
+            }
+            if from_sides & to_sides != 0 {
+                // Both ends are beyond the same side
+                return None;
+            }
+            // Moves an end beyond a side onto it, along the line
+            let sides = if from_sides != 0 {
+                from_sides
+            } else {
+                to_sides
+            };
+            let ((x0, y0), (x1, y1)) = (from, to);
+            let on_side = if sides & 0b0011 != 0 {
+                let x = if sides & 0b0001 != 0 { min_x } else { max_x };
+                (x, y0 + (y1 - y0) * (x - x0) / (x1 - x0))
+            } else {
+                let y = if sides & 0b0100 != 0 { min_y } else { max_y };
+                (x0 + (x1 - x0) * (y - y0) / (y1 - y0), y)
+            };
+            if from_sides != 0 {
+                from = on_side;
+            } else {
+                to = on_side;
+            }
+        }
+        None
+    }
+
+    /// Gets the characters of the cells with raised dots, the grid being at the given position
+    fn primitives(&self, x: i32, y: i32) -> Vec<Primitive> {
+        self.cells
+            .iter()
+            .zip(self.colors.iter())
+            .enumerate()
+            .filter(|(_, (dots, _))| **dots != 0)
+            .filter_map(|(idx, (dots, color))| {
+                let content = std::char::from_u32(BRAILLE_BLANK + *dots as u32)?;
+                Some(Primitive::Char(
+                    x + (idx % self.width) as i32,
+                    y + (idx / self.width) as i32,
+                    content,
+                    Style::new(*color),
+                ))
+            })
+            .collect()
+    }
+}
+
+impl<Message> Widget<Message, PancursesRenderer> for Canvas {
+    fn width(&self) -> Length {
+        self.width
+    }
+
+    fn height(&self) -> Length {
+        self.height
+    }
+
+    fn layout(&self, _renderer: &PancursesRenderer, limits: &layout::Limits) -> layout::Node {
+        let limits = limits.width(self.width).height(self.height);
+        layout::Node::new(limits.resolve(Size::ZERO))
+    }
+
+    fn draw(
+        &self,
+        _renderer: &mut PancursesRenderer,
+        layout: Layout<'_>,
+        _cursor_position: Point,
+    ) -> Primitive {
+        self.primitives(layout.bounds())
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        std::any::TypeId::of::<Canvas>().hash(state);
+        self.width.hash(state);
+        self.height.hash(state);
+    }
+}
+
+impl<'a, Message> From<Canvas> for Element<'a, Message, PancursesRenderer> {
+    fn from(canvas: Canvas) -> Element<'a, Message, PancursesRenderer> {
+        Element::new(canvas)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::{BrailleGrid, Canvas};
+    use crate::primitive::Primitive;
+    use crate::PancursesRenderer;
+    use iced_native::{Color, Rectangle};
+
+    #[test]
+    pub fn braille_grid_should_raise_dots() {
+        let mut grid = BrailleGrid::new(2, 1);
+        // The dots of a cell are numbered from top to bottom, then from left to right,
+        // the bottom line coming last
+        grid.set((0., 0.), Color::WHITE);
+        grid.set((1., 3.), Color::WHITE);
+        grid.line((2., 0.), (2., 3.), Color::WHITE);
+        assert_eq!(grid.cells, vec![0x81, 0x47]);
+
+        // Dots outside of the grid are ignored
+        grid.set((4., 0.), Color::WHITE);
+        grid.set((-1., 0.), Color::WHITE);
+        assert_eq!(grid.cells, vec![0x81, 0x47]);
+
+        // ... as well as invalid ones
+        grid.set((f32::NAN, 0.), Color::WHITE);
+        grid.line((f32::INFINITY, 0.), (0., 0.), Color::WHITE);
+        assert_eq!(grid.cells, vec![0x81, 0x47]);
+        let mut empty = BrailleGrid::new(0, 0);
+        empty.set((f32::NAN, f32::NAN), Color::WHITE);
+        empty.line((0., 0.), (1., 1.), Color::WHITE);
+    }
+
+    #[test]
+    pub fn braille_grid_should_clip_lines() {
+        let mut grid = BrailleGrid::new(2, 1);
+        // Only the part of a line on the grid is drawn, however far its ends are
+        grid.line((-1e30, 3.), (1e30, 3.), Color::WHITE);
+        assert_eq!(grid.cells, vec![0xc0, 0xc0]);
+        grid.line((-10., -10.), (-1., 10.), Color::WHITE);
+        assert_eq!(grid.cells, vec![0xc0, 0xc0]);
+    }
+
+    #[test]
+    pub fn canvas_should_draw_shapes() {
+        let red = Color::from_rgb(1., 0., 0.);
+        let canvas = Canvas::new()
+            .x_bounds(0., 3.)
+            .y_bounds(0., 3.)
+            .line(0., 0., 3., 0., Color::WHITE)
+            .point(3., 3., red)
+            .label(0., 3., "Hi", Color::WHITE);
+        let mut renderer = PancursesRenderer::headless(2, 1);
+        renderer.draw(canvas.primitives(Rectangle {
+            x: 0.,
+            y: 0.,
+            width: 2.,
+            height: 1.,
+        }));
+        // The line is at the bottom, the point at the top right under the label
+        let backend = renderer.headless_backend().unwrap();
+        assert_eq!(backend.lines(), vec!["Hi"]);
+
+        let canvas = Canvas::new()
+            .x_bounds(0., 3.)
+            .y_bounds(0., 3.)
+            .line(0., 0., 3., 0., Color::WHITE)
+            .point(3., 3., red);
+        renderer.draw(canvas.primitives(Rectangle {
+            x: 0.,
+            y: 0.,
+            width: 2.,
+            height: 1.,
+        }));
+        let backend = renderer.headless_backend().unwrap();
+        assert_eq!(backend.lines(), vec!["⣀⣈"]);
+        assert_eq!(backend.cell(1, 0).unwrap().style.foreground, red);
+    }
+
+    #[test]
+    pub fn canvas_should_ignore_invalid_shapes() {
+        let bounds = Rectangle {
+            x: 0.,
+            y: 0.,
+            width: 2.,
+            height: 1.,
+        };
+        // Nothing is drawn with bounds of the same coordinates
+        let canvas = Canvas::new()
+            .x_bounds(1., 1.)
+            .point(1., 0.5, Color::WHITE)
+            .label(1., 0.5, "Hi", Color::WHITE);
+        assert!(matches!(canvas.primitives(bounds), Primitive::Empty));
+
+        // ... nor in empty bounds
+        let canvas = Canvas::new().point(0.5, 0.5, Color::WHITE);
+        assert!(matches!(
+            canvas.primitives(Rectangle::default()),
+            Primitive::Empty
+        ));
+
+        // Huge circles are drawn with a limited number of segments, all off the canvas here
+        let canvas = Canvas::new().circle(0., 0., 1e9, Color::WHITE);
+        let mut renderer = PancursesRenderer::headless(2, 1);
+        renderer.draw(canvas.primitives(bounds));
+        assert_eq!(renderer.headless_backend().unwrap().lines(), vec!["  "]);
+    }
+}
@@ -0,0 +1,7 @@
+//! Widgets made for the terminal, only drawn by the `PancursesRenderer`
+
+mod big_text;
+mod canvas;
+
+pub use self::big_text::BigText;
+pub use self::canvas::Canvas;